@@ -0,0 +1,121 @@
+use glam::*;
+
+/// Maximum angle covered by a single segment of an arc.
+const MAX_SEGMENT_ANGLE: f32 = std::f32::consts::TAU / 64.0;
+
+/// Fills a pie slice, e.g. for radial progress indicators.
+///
+/// See [`ring`] for how angles are interpreted.
+pub fn pie(
+    template: &crate::Item,
+    center: Vec2,
+    radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+) -> Vec<crate::Item> {
+    ring(template, center, 0.0, radius, start_angle, end_angle)
+}
+
+/// Fills a section of a ring between two radii, e.g. for cooldown sweeps.
+///
+/// Each segment of the ring is a copy of the template item with [`crate::Item::corners`] set, so the template's source region should usually be a single solid texel. The template's transform is ignored.
+///
+/// Angles are in radians, increasing from the positive x axis towards the positive y axis. The ring is filled from `start_angle` to `end_angle`, wrapping around if `end_angle` is less than `start_angle`, and is a full ring if they differ by a multiple of a full turn.
+pub fn ring(
+    template: &crate::Item,
+    center: Vec2,
+    inner_radius: f32,
+    outer_radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+) -> Vec<crate::Item> {
+    let mut sweep = (end_angle - start_angle).rem_euclid(std::f32::consts::TAU);
+    if sweep == 0.0 {
+        if end_angle == start_angle {
+            return vec![];
+        }
+        sweep = std::f32::consts::TAU;
+    }
+
+    let segments = (sweep / MAX_SEGMENT_ANGLE).ceil().max(1.0) as usize;
+    let point = |angle: f32, radius: f32| center + Vec2::from_angle(angle) * radius;
+
+    (0..segments)
+        .map(|i| {
+            let start = start_angle + sweep * i as f32 / segments as f32;
+            let end = start_angle + sweep * (i + 1) as f32 / segments as f32;
+            crate::Item {
+                corners: Some([
+                    point(start, outer_radius),
+                    point(start, inner_radius),
+                    point(end, outer_radius),
+                    point(end, inner_radius),
+                ]),
+                ..template.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+
+    /// Gets the angles at which a ring starts and ends, and its number of segments.
+    fn sweep(start_angle: f32, end_angle: f32) -> Option<(f32, f32, usize)> {
+        let items = ring(
+            &crate::Item::default(),
+            Vec2::ZERO,
+            1.0,
+            2.0,
+            start_angle,
+            end_angle,
+        );
+        let angle = |corner: Vec2| corner.to_angle().rem_euclid(TAU);
+        Some((
+            angle(items.first()?.corners?[0]),
+            angle(items.last()?.corners?[2]),
+            items.len(),
+        ))
+    }
+
+    #[track_caller]
+    fn assert_sweep(start_angle: f32, end_angle: f32, expected: (f32, f32)) {
+        let (start, end, _) = sweep(start_angle, end_angle).unwrap();
+        assert!(
+            (start - expected.0).abs() < 1e-4 && (end - expected.1).abs() < 1e-4,
+            "{start} {end}"
+        );
+    }
+
+    #[test]
+    fn wraps_around_past_a_full_turn() {
+        // Rings go from the start angle towards increasing angles to the end angle, however the angles wrap.
+        assert_sweep(0.0, FRAC_PI_2, (0.0, FRAC_PI_2));
+        assert_sweep(TAU - FRAC_PI_4, FRAC_PI_4, (TAU - FRAC_PI_4, FRAC_PI_4));
+        assert_sweep(0.0, TAU + FRAC_PI_2, (0.0, FRAC_PI_2));
+        assert_sweep(FRAC_PI_2, -TAU, (FRAC_PI_2, 0.0));
+        assert_eq!(sweep(0.0, FRAC_PI_2).unwrap().2, 16);
+        assert_eq!(sweep(FRAC_PI_2, 0.0).unwrap().2, 48);
+    }
+
+    #[test]
+    fn full_and_empty_rings() {
+        for end_angle in [FRAC_PI_2 + TAU, FRAC_PI_2 - 2.0 * TAU] {
+            assert_sweep(FRAC_PI_2, end_angle, (FRAC_PI_2, FRAC_PI_2));
+            assert_eq!(sweep(FRAC_PI_2, end_angle).unwrap().2, 64);
+        }
+        assert_eq!(sweep(1.0, 1.0), None);
+    }
+
+    #[test]
+    fn pie_slices_meet_at_the_center() {
+        let center = vec2(5.0, 6.0);
+        let items = pie(&crate::Item::default(), center, 3.0, 0.0, FRAC_PI_2);
+        assert!(items.iter().all(|item| {
+            let [_, inner_start, _, inner_end] = item.corners.unwrap();
+            inner_start == center && inner_end == center
+        }));
+    }
+}
@@ -0,0 +1,275 @@
+use glam::*;
+
+/// Gap left between entries in the atlas.
+const PADDING: u32 = 1;
+
+/// A region of an [`Atlas`] that a texture was copied into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasHandle {
+    /// Offset of the region in the atlas texture.
+    pub offset: IVec2,
+
+    /// Size of the region.
+    pub size: UVec2,
+
+    /// Layer of the atlas texture the region is in.
+    pub layer: u32,
+}
+
+impl AtlasHandle {
+    /// Creates an item that draws the whole region.
+    pub fn item(&self, transform: Affine2, tint: crate::Color) -> crate::Item {
+        crate::Item {
+            src_offset: self.offset,
+            src_size: self.size,
+            src_layer: self.layer,
+            transform,
+            tint,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+/// Packs entries into shelves on each layer of an atlas, without touching the texture.
+#[derive(Debug, Clone)]
+struct ShelfAllocator {
+    size: UVec2,
+    layers: Vec<Vec<Shelf>>,
+}
+
+impl ShelfAllocator {
+    fn new(size: UVec2, layers: u32) -> Self {
+        Self {
+            size,
+            layers: vec![vec![]; layers as usize],
+        }
+    }
+
+    fn clear(&mut self) {
+        for shelves in self.layers.iter_mut() {
+            shelves.clear();
+        }
+    }
+
+    /// Reserves a region of the given size, or returns `None` if there is no space left.
+    ///
+    /// Empty regions don't take up any space.
+    fn allocate(&mut self, size: UVec2) -> Option<AtlasHandle> {
+        if size.x == 0 || size.y == 0 {
+            return Some(AtlasHandle {
+                offset: IVec2::ZERO,
+                size,
+                layer: 0,
+            });
+        }
+
+        if size.x > self.size.x || size.y > self.size.y {
+            return None;
+        }
+
+        for (layer, shelves) in self.layers.iter_mut().enumerate() {
+            if let Some(shelf) = shelves
+                .iter_mut()
+                .find(|shelf| size.y <= shelf.height && shelf.x + size.x <= self.size.x)
+            {
+                let offset = ivec2(shelf.x as i32, shelf.y as i32);
+                shelf.x += size.x + PADDING;
+                return Some(AtlasHandle {
+                    offset,
+                    size,
+                    layer: layer as u32,
+                });
+            }
+
+            let y = shelves
+                .last()
+                .map(|shelf| shelf.y + shelf.height + PADDING)
+                .unwrap_or(0);
+            if y + size.y <= self.size.y {
+                shelves.push(Shelf {
+                    y,
+                    height: size.y,
+                    x: size.x + PADDING,
+                });
+                return Some(AtlasHandle {
+                    offset: ivec2(0, y as i32),
+                    size,
+                    layer: layer as u32,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+/// A runtime texture atlas.
+///
+/// Many small textures can be copied into a shared atlas texture so that sprites drawn from them can be put into the same [`crate::Group`] and drawn with a single draw call.
+///
+/// Entries are packed into shelves, so the atlas works best when entries have similar heights.
+///
+/// An atlas created with multiple layers fills them in order as pages. All layers belong to one texture, so entries on different pages can still be drawn in the same group, e.g. for glyph atlases that span several pages.
+///
+/// Atlases are kept separate from [`crate::Renderer`] rather than managed by it, so that an application can have as many as it needs, e.g. one per texture format, and choose when each is cleared. Groups drawing from an atlas are batched like groups drawing from any other texture.
+pub struct Atlas {
+    texture: wgpu::Texture,
+    shelves: ShelfAllocator,
+}
+
+impl Atlas {
+    /// Creates a new atlas with the given size and format.
+    ///
+    /// Textures inserted into the atlas must have the same format.
+    pub fn new(device: &wgpu::Device, size: wgpu::Extent3d, format: wgpu::TextureFormat) -> Self {
+        Self {
+            texture: device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("spright: atlas"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            }),
+            shelves: ShelfAllocator::new(
+                uvec2(size.width, size.height),
+                size.depth_or_array_layers,
+            ),
+        }
+    }
+
+    /// Gets the atlas texture, for use in a [`crate::Group`].
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Copies the first layer of a texture into the atlas.
+    ///
+    /// The texture must have been created with [`wgpu::TextureUsages::COPY_SRC`] and have the same format as the atlas. The copy is submitted right away, so use [`Atlas::insert_with_encoder`] to insert many textures with one submission.
+    ///
+    /// Returns `None` if there is no space left in the atlas.
+    pub fn insert(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> Option<AtlasHandle> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("spright: atlas insert"),
+        });
+        let handle = self.insert_with_encoder(&mut encoder, texture)?;
+        queue.submit(Some(encoder.finish()));
+        Some(handle)
+    }
+
+    /// Copies the first layer of a texture into the atlas like [`Atlas::insert`], but records the copy into a command encoder instead of submitting it.
+    ///
+    /// The encoder must be submitted before any commands drawing from the returned region.
+    pub fn insert_with_encoder(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Option<AtlasHandle> {
+        assert_eq!(
+            texture.format(),
+            self.texture.format(),
+            "spright: atlas entries must match the atlas format"
+        );
+
+        let size = uvec2(texture.width(), texture.height());
+        let handle = self.shelves.allocate(size)?;
+
+        encoder.copy_texture_to_texture(
+            texture.as_image_copy(),
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: handle.offset.x as u32,
+                    y: handle.offset.y as u32,
+                    z: handle.layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Some(handle)
+    }
+
+    /// Forgets all entries in the atlas, so that its space can be reused.
+    ///
+    /// Handles returned before clearing must not be used afterwards.
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_a_shelf_then_starts_a_new_one() {
+        let mut shelves = ShelfAllocator::new(uvec2(16, 16), 1);
+        let offsets = (0..4)
+            .map(|_| shelves.allocate(uvec2(7, 4)).unwrap().offset)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            offsets,
+            [ivec2(0, 0), ivec2(8, 0), ivec2(0, 5), ivec2(8, 5)]
+        );
+
+        // A shorter entry fits into an existing shelf with space left, and a taller one needs a new shelf.
+        let mut shelves = ShelfAllocator::new(uvec2(16, 16), 1);
+        shelves.allocate(uvec2(4, 4)).unwrap();
+        assert_eq!(shelves.allocate(uvec2(4, 2)).unwrap().offset, ivec2(5, 0));
+        assert_eq!(shelves.allocate(uvec2(4, 6)).unwrap().offset, ivec2(0, 5));
+    }
+
+    #[test]
+    fn returns_none_when_full() {
+        let mut shelves = ShelfAllocator::new(uvec2(8, 8), 1);
+        assert_eq!(shelves.allocate(uvec2(9, 1)), None);
+        assert_eq!(shelves.allocate(uvec2(1, 9)), None);
+
+        shelves.allocate(uvec2(8, 4)).unwrap();
+        shelves.allocate(uvec2(8, 3)).unwrap();
+        assert_eq!(shelves.allocate(uvec2(1, 1)), None);
+
+        shelves.clear();
+        assert_eq!(shelves.allocate(uvec2(8, 8)).unwrap().offset, IVec2::ZERO);
+    }
+
+    #[test]
+    fn fills_layers_in_order() {
+        let mut shelves = ShelfAllocator::new(uvec2(8, 8), 2);
+        assert_eq!(shelves.allocate(uvec2(8, 8)).unwrap().layer, 0);
+        assert_eq!(shelves.allocate(uvec2(8, 8)).unwrap().layer, 1);
+        assert_eq!(shelves.allocate(uvec2(8, 8)), None);
+    }
+
+    #[test]
+    fn empty_entries_take_no_space() {
+        let mut shelves = ShelfAllocator::new(uvec2(8, 8), 1);
+        for size in [uvec2(0, 0), uvec2(0, 8), uvec2(8, 0)] {
+            assert_eq!(shelves.allocate(size).unwrap().size, size);
+        }
+        assert_eq!(shelves.allocate(uvec2(8, 8)).unwrap().offset, IVec2::ZERO);
+    }
+}
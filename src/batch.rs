@@ -7,13 +7,16 @@ pub struct Sprite<'a> {
     /// Texture to draw with.
     pub texture: &'a wgpu::Texture,
 
+    /// Texture to take alpha from, see [`crate::Group::alpha_texture`].
+    pub alpha_texture: Option<&'a wgpu::Texture>,
+
     /// Source offset from the texture.
     pub src_offset: IVec2,
 
     /// Source size.
     pub src_size: UVec2,
 
-    /// Source layer.
+    /// Source layer, see [`crate::Item::src_layer`].
     pub src_layer: u32,
 
     /// Target transform.
@@ -21,29 +24,327 @@ pub struct Sprite<'a> {
 
     /// Tint.
     pub tint: crate::Color,
+
+    /// Floating point tint, used instead of [`Sprite::tint`] if set.
+    pub tint_f32: Option<Vec4>,
+
+    /// Tints for each corner, used instead of [`Sprite::tint`] and [`Sprite::tint_f32`] if set.
+    pub tints: Option<[crate::Color; 4]>,
+
+    /// Blend mode.
+    pub blend_mode: crate::BlendMode,
+
+    /// Pick ID.
+    pub pick_id: u32,
+
+    /// Depth in clip space.
+    pub z: f32,
+
+    /// Rotation of the source region in the texture.
+    pub src_rotation: crate::SrcRotation,
+
+    /// Target corners, used instead of transforming the source region if set.
+    pub corners: Option<[Vec2; 4]>,
+
+    /// Number of columns and rows the quad is divided into.
+    pub subdivisions: UVec2,
+
+    /// Part of the source region to show, see [`crate::Item::reveal`].
+    pub reveal: Option<crate::Rect>,
+
+    /// Texture scroll within the sprite, see [`crate::Item::uv_scroll`].
+    pub uv_scroll: Vec2,
+
+    /// Sort key used by [`batch_by_order`], with lower orders drawn first.
+    pub order: f32,
+}
+
+impl<'a> Sprite<'a> {
+    /// Creates a sprite drawing a source region of a texture with a transform and tint, and defaults for everything else.
+    pub fn new(
+        texture: &'a wgpu::Texture,
+        src: crate::Rect,
+        transform: Affine2,
+        tint: crate::Color,
+    ) -> Self {
+        Self {
+            texture,
+            alpha_texture: None,
+            src_offset: src.offset,
+            src_size: src.size,
+            src_layer: 0,
+            transform,
+            tint,
+            tint_f32: None,
+            tints: None,
+            blend_mode: crate::BlendMode::default(),
+            pick_id: 0,
+            z: 0.0,
+            src_rotation: crate::SrcRotation::None,
+            corners: None,
+            subdivisions: UVec2::ONE,
+            reveal: None,
+            uv_scroll: Vec2::ZERO,
+            order: 0.0,
+        }
+    }
+
+    /// Creates a sprite from a source region of a texture, scaled and centered at a point.
+    pub fn centered_at(
+        texture: &'a wgpu::Texture,
+        src: crate::Rect,
+        center: Vec2,
+        scale: Vec2,
+        tint: crate::Color,
+    ) -> Self {
+        Self::new(
+            texture,
+            src,
+            Affine2::from_scale_angle_translation(
+                scale,
+                0.0,
+                center - src.size.as_vec2() * scale / 2.0,
+            ),
+            tint,
+        )
+    }
 }
 
-/// Batches a flat list of [`Sprite`]s into groups with textures.
+/// Builds [`Sprite`]s that share a texture, alpha texture and blend mode, e.g. for particle emitters.
+///
+/// The shared state is set once and filled into every sprite, so the sprites are guaranteed to batch together when they're consecutive.
+#[derive(Debug, Clone)]
+pub struct SpriteBuilder<'a> {
+    texture: &'a wgpu::Texture,
+    alpha_texture: Option<&'a wgpu::Texture>,
+    blend_mode: crate::BlendMode,
+    tint: crate::Color,
+    sprites: Vec<Sprite<'a>>,
+}
+
+impl<'a> SpriteBuilder<'a> {
+    /// Creates a builder for sprites drawn from a texture with the default blend mode and a white tint.
+    pub fn new(texture: &'a wgpu::Texture) -> Self {
+        Self {
+            texture,
+            alpha_texture: None,
+            blend_mode: crate::BlendMode::default(),
+            tint: crate::Color::new(0xff, 0xff, 0xff, 0xff),
+            sprites: vec![],
+        }
+    }
+
+    /// Sets the alpha texture of the sprites, see [`crate::Group::alpha_texture`].
+    pub fn alpha_texture(mut self, alpha_texture: &'a wgpu::Texture) -> Self {
+        self.alpha_texture = Some(alpha_texture);
+        self
+    }
+
+    /// Sets the blend mode of the sprites.
+    pub fn blend_mode(mut self, blend_mode: crate::BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// Sets the tint of sprites added with [`SpriteBuilder::sprite`], e.g. a theme color shared by many sprites.
+    ///
+    /// This defaults to opaque white, which leaves colors unchanged and draws masks in white.
+    pub fn tint(mut self, tint: crate::Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Adds a sprite drawing a source region of the texture with a transform and the builder's tint.
+    pub fn sprite(&mut self, src: crate::Rect, transform: Affine2) -> &mut Self {
+        self.tinted_sprite(src, transform, self.tint)
+    }
+
+    /// Adds a sprite drawing a source region of the texture with a transform and its own tint.
+    pub fn tinted_sprite(
+        &mut self,
+        src: crate::Rect,
+        transform: Affine2,
+        tint: crate::Color,
+    ) -> &mut Self {
+        self.sprites.push(Sprite {
+            alpha_texture: self.alpha_texture,
+            blend_mode: self.blend_mode,
+            ..Sprite::new(self.texture, src, transform, tint)
+        });
+        self
+    }
+
+    /// Gets the sprites added so far, in the order they were added.
+    pub fn sprites(&self) -> &[Sprite<'a>] {
+        &self.sprites
+    }
+
+    /// Takes the sprites added so far, e.g. to modify them further.
+    pub fn into_sprites(self) -> Vec<Sprite<'a>> {
+        self.sprites
+    }
+}
+
+/// Batches a flat list of [`Sprite`]s into groups with textures, alpha textures and blend modes.
+///
+/// Sprites drawing from different layers of the same texture are batched together.
+///
+/// Only consecutive sprites are batched together, so the submission order of sprites is preserved exactly: groups are in the order of their first sprite, and sprites within a group keep their order. Sprites are never reordered to reduce the number of groups, as that would change how overlapping sprites are drawn. Use [`batch_by_order`] to sort sprites first.
 pub fn batch<'a>(sprites: &'a [Sprite]) -> Vec<crate::Group<'a>> {
-    sprites
-        .iter()
-        .chunk_by(|s| s.texture)
+    batch_with(sprites, &Consecutive)
+}
+
+/// Sorts [`Sprite`]s by [`Sprite::order`] and then batches them like [`batch`], e.g. to draw transparent sprites back to front.
+///
+/// Batching and sorting pull in opposite directions: sprites can only share a group if they are consecutive after sorting, so sprites with distinct orders that alternate between textures each need their own group. Giving sprites that don't overlap the same order lets them batch together. Sprites with equal orders keep their submission order.
+pub fn batch_by_order<'a>(sprites: &'a [Sprite]) -> Vec<crate::Group<'a>> {
+    batch_with(sprites, &ByOrder)
+}
+
+/// Decides the order sprites are batched in by [`batch_with`].
+///
+/// Consecutive sprites in the arranged order are put into the same group if they share their texture, alpha texture and blend mode, so a strategy controls how many groups are needed by how it reorders sprites.
+pub trait BatchStrategy {
+    /// Arranges sprites in the order they are drawn in.
+    ///
+    /// Every sprite should be returned exactly once.
+    fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>>;
+}
+
+/// Keeps sprites in their submission order, as done by [`batch`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Consecutive;
+
+impl BatchStrategy for Consecutive {
+    fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+        sprites.iter().collect()
+    }
+}
+
+/// Sorts sprites by [`Sprite::order`], as done by [`batch_by_order`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByOrder;
+
+impl BatchStrategy for ByOrder {
+    fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+        let mut sorted = sprites.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.order.total_cmp(&b.order));
+        sorted
+    }
+}
+
+/// Sorts sprites by texture, alpha texture and blend mode, so that each combination is drawn in exactly one group.
+///
+/// This needs the fewest groups, but the order textures are drawn in is arbitrary, so it's only suitable for sprites that don't overlap or that are depth tested. Sprites sharing a texture keep their submission order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByTexture;
+
+impl BatchStrategy for ByTexture {
+    fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+        let mut sorted = sprites.iter().collect::<Vec<_>>();
+        sorted.sort_by_cached_key(|s| {
+            (
+                crate::TextureId::of(s.texture),
+                s.alpha_texture.map(crate::TextureId::of),
+                s.blend_mode as u8,
+            )
+        });
+        sorted
+    }
+}
+
+/// Batches [`Sprite`]s into groups like [`batch`], after arranging them with a [`BatchStrategy`].
+///
+/// A new group is started wherever the texture, alpha texture or blend mode changes between consecutive arranged sprites, so whatever order a strategy picks, every sprite in a group shares its group's textures and blend mode. This is also checked in debug builds, to catch grouping bugs.
+///
+/// # Panics
+///
+/// In debug builds, panics if the strategy drops or duplicates sprites.
+pub fn batch_with<'a>(
+    sprites: &'a [Sprite],
+    strategy: &impl BatchStrategy,
+) -> Vec<crate::Group<'a>> {
+    let groups = strategy
+        .arrange(sprites)
         .into_iter()
-        .map(|(_, chunk)| {
-            let chunk = chunk.collect::<Vec<_>>();
-            crate::Group {
-                texture: chunk.first().unwrap().texture,
-                items: chunk
-                    .into_iter()
-                    .map(|s| crate::Item {
-                        src_offset: s.src_offset,
-                        src_size: s.src_size,
-                        src_layer: s.src_layer,
-                        transform: s.transform,
-                        tint: s.tint,
-                    })
-                    .collect::<Vec<_>>(),
-            }
-        })
-        .collect::<Vec<_>>()
+        .chunk_by(|s| (s.texture, s.alpha_texture, s.blend_mode))
+        .into_iter()
+        .map(
+            |((texture, alpha_texture, blend_mode), chunk)| crate::Group {
+                blend_mode,
+                alpha_texture,
+                ..crate::Group::new(
+                    texture,
+                    chunk
+                        .inspect(|s| {
+                            debug_assert!(
+                                crate::TextureId::of(s.texture) == crate::TextureId::of(texture)
+                                    && s.alpha_texture.map(crate::TextureId::of)
+                                        == alpha_texture.map(crate::TextureId::of)
+                                    && s.blend_mode == blend_mode,
+                                "spright: batched sprite does not share its group's texture or blend mode"
+                            )
+                        })
+                        .map(|s| crate::Item {
+                            src_offset: s.src_offset,
+                            src_size: s.src_size,
+                            src_layer: s.src_layer,
+                            transform: s.transform,
+                            tint: s.tint,
+                            tint_f32: s.tint_f32,
+                            tints: s.tints,
+                            pick_id: s.pick_id,
+                            z: s.z,
+                            src_rotation: s.src_rotation,
+                            corners: s.corners,
+                            subdivisions: s.subdivisions,
+                            reveal: s.reveal,
+                            uv_scroll: s.uv_scroll,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            },
+        )
+        .collect::<Vec<_>>();
+    debug_assert_eq!(
+        groups.iter().map(|group| group.items.len()).sum::<usize>(),
+        sprites.len(),
+        "spright: batch strategy must not drop or duplicate sprites"
+    );
+    groups
+}
+
+/// Accumulates sprites from multiple sources before they are batched, so that a frame assembled by several subsystems is still prepared with a single upload.
+///
+/// The batch can be cleared and reused across frames to keep its allocation.
+#[derive(Debug, Clone, Default)]
+pub struct SpriteBatch<'a> {
+    sprites: Vec<Sprite<'a>>,
+}
+
+impl<'a> SpriteBatch<'a> {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends sprites, to be drawn after any sprites already in the batch.
+    pub fn push(&mut self, sprites: &[Sprite<'a>]) {
+        self.sprites.extend_from_slice(sprites);
+    }
+
+    /// Removes all sprites from the batch.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// Gets the sprites in the batch, in the order they were appended.
+    pub fn sprites(&self) -> &[Sprite<'a>] {
+        &self.sprites
+    }
+
+    /// Batches the sprites into groups with [`batch`], for passing to [`crate::Renderer::prepare`].
+    pub fn groups(&self) -> Vec<crate::Group<'_>> {
+        batch(&self.sprites)
+    }
 }
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::TextureId;
+
+/// Identifies a texture bind group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct BindGroupKey {
+    pub texture: TextureId,
+    pub alpha_texture: Option<TextureId>,
+    /// ID of the external sampler, if any.
+    pub sampler: Option<u64>,
+}
+
+/// Properties of a texture that are checked before reusing a cached bind group.
+///
+/// As IDs may be reused after a texture is dropped, this catches most cases of a new texture taking the place of an old one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    mip_level_count: u32,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+}
+
+impl Fingerprint {
+    fn of(texture: &wgpu::Texture) -> Self {
+        Self {
+            size: texture.size(),
+            format: texture.format(),
+            mip_level_count: texture.mip_level_count(),
+            sample_count: texture.sample_count(),
+            usage: texture.usage(),
+        }
+    }
+}
+
+struct Entry {
+    bind_group: wgpu::BindGroup,
+    fingerprint: (Fingerprint, Option<Fingerprint>),
+    used: bool,
+}
+
+/// Texture bind groups, optionally cached across frames.
+pub(crate) struct BindGroups {
+    cache: bool,
+    entries: HashMap<BindGroupKey, Entry>,
+}
+
+impl BindGroups {
+    pub fn new(cache: bool) -> Self {
+        Self {
+            cache,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Starts preparing a frame.
+    ///
+    /// If caching is disabled, all bind groups from the previous frame are dropped.
+    pub fn begin(&mut self) {
+        if !self.cache {
+            self.entries.clear();
+        }
+        for entry in self.entries.values_mut() {
+            entry.used = false;
+        }
+    }
+
+    /// Finishes preparing a frame, dropping bind groups that weren't used in it.
+    pub fn end(&mut self) {
+        self.entries.retain(|_, entry| entry.used);
+    }
+
+    /// Ensures a bind group exists for the given key, creating it if needed.
+    pub fn ensure(
+        &mut self,
+        key: BindGroupKey,
+        texture: &wgpu::Texture,
+        alpha_texture: Option<&wgpu::Texture>,
+        create: impl FnOnce() -> wgpu::BindGroup,
+    ) {
+        let fingerprint = (Fingerprint::of(texture), alpha_texture.map(Fingerprint::of));
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.fingerprint == fingerprint => {
+                entry.used = true;
+            }
+            _ => {
+                self.entries.insert(
+                    key,
+                    Entry {
+                        bind_group: create(),
+                        fingerprint,
+                        used: true,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Gets a bind group that was previously created with [`BindGroups::ensure`].
+    pub fn get(&self, key: &BindGroupKey) -> &wgpu::BindGroup {
+        &self.entries[key].bind_group
+    }
+
+    /// Drops all bind groups using a texture.
+    pub fn invalidate_texture(&mut self, texture: TextureId) {
+        self.entries
+            .retain(|key, _| key.texture != texture && key.alpha_texture != Some(texture));
+    }
+
+    /// Drops all bind groups.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
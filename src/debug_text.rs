@@ -0,0 +1,176 @@
+use glam::*;
+
+/// Size of each glyph cell in the font texture, including one texel of spacing to the right and below.
+pub const GLYPH_SIZE: UVec2 = uvec2(6, 8);
+
+/// Number of glyph cells per row of the font texture.
+const COLUMNS: u32 = 16;
+
+/// First character in the font.
+const FIRST: u8 = b' ';
+
+/// Glyphs for `' '` to `'~'`, 5 columns of 7 rows each from the left, with the top row in the lowest bit.
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00], [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14], [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e], [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x01, 0x01], [0x3e, 0x41, 0x41, 0x51, 0x32],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00], [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40], [0x7f, 0x02, 0x04, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f], [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x7f, 0x20, 0x18, 0x20, 0x7f],
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x03, 0x04, 0x78, 0x04, 0x03], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78], [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f], [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x08, 0x14, 0x54, 0x54, 0x3c],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00], [0x00, 0x7f, 0x10, 0x28, 0x44],
+    [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78], [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08], [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c], [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c], [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+/// A tiny built-in fixed-width bitmap font for debug overlays, e.g. frame times and coordinates.
+///
+/// This covers printable ASCII only and is not meant for production text. Glyphs are drawn from an [`wgpu::TextureFormat::R8Unorm`] mask, so they take the color of their tint.
+pub struct DebugFont {
+    texture: wgpu::Texture,
+}
+
+impl DebugFont {
+    /// Creates the font texture.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let size = uvec2(COLUMNS, (GLYPHS.len() as u32).div_ceil(COLUMNS)) * GLYPH_SIZE;
+        let mut data = vec![0; (size.x * size.y) as usize];
+        for (i, glyph) in GLYPHS.iter().enumerate() {
+            let cell = uvec2(i as u32 % COLUMNS, i as u32 / COLUMNS) * GLYPH_SIZE;
+            for (x, column) in glyph.iter().enumerate() {
+                for y in 0..7 {
+                    if column & (1 << y) != 0 {
+                        data[((cell.y + y) * size.x + cell.x + x as u32) as usize] = 0xff;
+                    }
+                }
+            }
+        }
+        Self {
+            texture: crate::texture::create_layered_texture(
+                device,
+                queue,
+                "spright: debug_font_texture",
+                size,
+                wgpu::TextureFormat::R8Unorm,
+                &data,
+            ),
+        }
+    }
+
+    /// Gets the font texture, for use in a [`crate::Group`].
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Lays out text with its top-left corner at a position, with each texel of the font scaled to `scale` target pixels.
+    ///
+    /// Newlines start a new line, and characters outside of printable ASCII are drawn as `'?'`. Whole number scales keep the glyphs crisp with the default nearest sampler.
+    pub fn text(
+        &self,
+        text: &str,
+        position: Vec2,
+        scale: f32,
+        tint: crate::Color,
+    ) -> Vec<crate::Item> {
+        layout(text, position, scale, tint)
+    }
+}
+
+/// Lays out text for [`DebugFont::text`], which doesn't depend on the font texture.
+fn layout(text: &str, position: Vec2, scale: f32, tint: crate::Color) -> Vec<crate::Item> {
+    let mut items = vec![];
+    let mut cursor = UVec2::ZERO;
+    for c in text.chars() {
+        if c == '\n' {
+            cursor = uvec2(0, cursor.y + 1);
+            continue;
+        }
+        let index = if (' '..='~').contains(&c) {
+            c as u32 - FIRST as u32
+        } else {
+            '?' as u32 - FIRST as u32
+        };
+        if c != ' ' {
+            items.push(crate::Item {
+                src_offset: (uvec2(index % COLUMNS, index / COLUMNS) * GLYPH_SIZE).as_ivec2(),
+                src_size: GLYPH_SIZE,
+                transform: Affine2::from_scale_angle_translation(
+                    Vec2::splat(scale),
+                    0.0,
+                    position + (cursor * GLYPH_SIZE).as_vec2() * scale,
+                ),
+                tint,
+                ..Default::default()
+            });
+        }
+        cursor.x += 1;
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the source offset and the position of each glyph.
+    fn glyphs(text: &str, position: Vec2, scale: f32) -> Vec<(IVec2, Vec2)> {
+        layout(text, position, scale, crate::Color::new(255, 255, 255, 255))
+            .iter()
+            .map(|item| (item.src_offset, item.transform.translation))
+            .collect()
+    }
+
+    #[test]
+    fn advances_by_glyph_width() {
+        // 'A' is glyph 33, in the third row of 16.
+        assert_eq!(
+            glyphs("A A", vec2(10.0, 20.0), 2.0),
+            [
+                (ivec2(6, 16), vec2(10.0, 20.0)),
+                (ivec2(6, 16), vec2(34.0, 20.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn newlines_return_to_the_start() {
+        assert_eq!(
+            glyphs("!\n\n!!", vec2(10.0, 20.0), 1.0),
+            [
+                (ivec2(6, 0), vec2(10.0, 20.0)),
+                (ivec2(6, 0), vec2(10.0, 36.0)),
+                (ivec2(6, 0), vec2(16.0, 36.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_characters_are_question_marks() {
+        // '?' is glyph 31, at the end of the second row.
+        let question_mark = ivec2(90, 8);
+        assert_eq!(
+            glyphs("é\t?", Vec2::ZERO, 1.0),
+            [
+                (question_mark, vec2(0.0, 0.0)),
+                (question_mark, vec2(6.0, 0.0)),
+                (question_mark, vec2(12.0, 0.0)),
+            ]
+        );
+        assert!(glyphs("   ", Vec2::ZERO, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use glam::*;
+
+/// How a source is fitted into a destination rectangle by [`fit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FitMode {
+    /// Stretches the source to exactly cover the destination, ignoring its aspect ratio.
+    #[default]
+    Fill,
+
+    /// Scales the source to fit entirely inside the destination while preserving its aspect ratio, centering it and leaving empty bars on two sides if the aspect ratios differ.
+    Contain,
+
+    /// Scales the source to cover the entire destination while preserving its aspect ratio, centering it and extending it past two sides if the aspect ratios differ.
+    ///
+    /// The parts outside the destination are not cropped by the transform, so a scissor rectangle or a smaller source region is needed to hide them.
+    Cover,
+}
+
+/// Computes a transform that maps a source of the given size into a destination rectangle, e.g. for letterboxing and thumbnails.
+///
+/// The result can be used as [`crate::Item::transform`]. If the source is empty, the transform scales it to nothing at the destination's offset.
+pub fn fit(src_size: UVec2, dest: crate::Rect, mode: FitMode) -> Affine2 {
+    let src_size = src_size.as_vec2();
+    let dest_offset = dest.offset.as_vec2();
+    let dest_size = dest.size.as_vec2();
+    if src_size.x <= 0.0 || src_size.y <= 0.0 {
+        return Affine2::from_scale_angle_translation(Vec2::ZERO, 0.0, dest_offset);
+    }
+
+    let ratio = dest_size / src_size;
+    let scale = match mode {
+        FitMode::Fill => ratio,
+        FitMode::Contain => Vec2::splat(ratio.min_element()),
+        FitMode::Cover => Vec2::splat(ratio.max_element()),
+    };
+    Affine2::from_scale_angle_translation(
+        scale,
+        0.0,
+        dest_offset + (dest_size - src_size * scale) / 2.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_modes() {
+        // A 2:1 source in a square destination.
+        let dest = crate::Rect::new(ivec2(10, 20), uvec2(8, 8));
+        for (mode, scale, translation) in [
+            (FitMode::Fill, vec2(2.0, 4.0), vec2(10.0, 20.0)),
+            (FitMode::Contain, vec2(2.0, 2.0), vec2(10.0, 22.0)),
+            (FitMode::Cover, vec2(4.0, 4.0), vec2(6.0, 20.0)),
+        ] {
+            assert_eq!(
+                fit(uvec2(4, 2), dest, mode),
+                Affine2::from_scale_angle_translation(scale, 0.0, translation),
+                "{mode:?}"
+            );
+        }
+
+        // With matching aspect ratios, all modes agree.
+        for mode in [FitMode::Fill, FitMode::Contain, FitMode::Cover] {
+            assert_eq!(
+                fit(uvec2(2, 2), dest, mode),
+                Affine2::from_scale_angle_translation(Vec2::splat(4.0), 0.0, vec2(10.0, 20.0)),
+                "{mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_sources() {
+        let dest = crate::Rect::new(ivec2(10, 20), uvec2(8, 8));
+        for src_size in [uvec2(0, 2), uvec2(2, 0)] {
+            assert_eq!(
+                fit(src_size, dest, FitMode::Contain),
+                Affine2::from_scale_angle_translation(Vec2::ZERO, 0.0, vec2(10.0, 20.0))
+            );
+        }
+    }
+}
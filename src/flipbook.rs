@@ -0,0 +1,37 @@
+use glam::*;
+
+/// A flipbook animation stored as a horizontal strip of equally sized frames in one texture.
+///
+/// Frames are selected with [`crate::Group::uv_offset`] rather than by changing each item's source region, so switching frames only changes the group's uniforms and all items in the group show the same frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flipbook {
+    /// Offset of the first frame in the texture.
+    pub offset: IVec2,
+
+    /// Size of each frame.
+    pub frame_size: UVec2,
+
+    /// Number of frames in the strip.
+    pub frame_count: u32,
+}
+
+impl Flipbook {
+    /// Creates an item showing the first frame, to be drawn in a group with [`Flipbook::uv_offset`].
+    pub fn item(&self) -> crate::Item {
+        crate::Item {
+            src_offset: self.offset,
+            src_size: self.frame_size,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the [`crate::Group::uv_offset`] that shows a frame.
+    ///
+    /// Frames wrap around, so an ever-increasing frame counter loops the animation.
+    pub fn uv_offset(&self, frame: u32) -> Vec2 {
+        vec2(
+            ((frame % self.frame_count.max(1)) * self.frame_size.x) as f32,
+            0.0,
+        )
+    }
+}
@@ -0,0 +1,543 @@
+use glam::*;
+
+use crate::{Color, Group, Item, SrcRotation, Vertex};
+
+/// Indices of the two triangles of a quad whose four vertices are in the order top-left, bottom-left, top-right, bottom-right, as generated for each item.
+///
+/// These wind counterclockwise in clip space for quads that aren't mirrored. Quads that are mirrored, or that are generated for [`wgpu::FrontFace::Cw`], use [`QUAD_INDICES_FLIPPED`] instead, so that every quad is front-facing.
+pub const QUAD_INDICES: [u32; 6] = [0, 1, 2, 1, 3, 2];
+
+/// Indices of the two triangles of a quad with the opposite winding of [`QUAD_INDICES`].
+pub const QUAD_INDICES_FLIPPED: [u32; 6] = [0, 2, 1, 1, 2, 3];
+
+/// Options for [`build_geometry`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeometryOptions {
+    /// Transform applied on top of each item's transform, e.g. to scale logical pixels to physical pixels.
+    pub view: Affine2,
+
+    /// Winding order of front-facing triangles, see [`crate::Options::front_face`].
+    pub front_face: wgpu::FrontFace,
+
+    /// Writes pick IDs instead of tints, see [`crate::Options::picking`].
+    pub picking: bool,
+
+    /// Converts tints from sRGB to linear, see [`crate::Options::srgb_tints`].
+    pub srgb_tints: bool,
+
+    /// Rounds corners to a grid of this many target pixels, see [`crate::Renderer::set_pixel_snap`].
+    pub pixel_snap: Option<f32>,
+}
+
+/// Vertices and indices generated for groups of sprites.
+#[derive(Debug, Clone, Default)]
+pub struct Geometry {
+    /// Vertices of all groups.
+    pub vertices: Vec<Vertex>,
+
+    /// Indices into [`Geometry::vertices`] of all groups.
+    pub indices: Vec<u32>,
+
+    /// Range of [`Geometry::indices`] for each group.
+    pub groups: Vec<std::ops::Range<u32>>,
+
+    /// Range of [`Geometry::vertices`] for each item of all groups, in order.
+    ///
+    /// Items that were skipped have empty ranges.
+    pub items: Vec<std::ops::Range<u32>>,
+}
+
+/// Generates the geometry that [`crate::Renderer::prepare`] uploads for groups of sprites.
+///
+/// This doesn't touch the GPU, so e.g. layout code can be tested without a device. Positions are in target pixels and are only normalized by the shader, so the target size is not needed.
+pub fn build_geometry(groups: &[Group<'_>], options: &GeometryOptions) -> Geometry {
+    let mut geometry = Geometry::default();
+
+    for group in groups {
+        let index_buffer_start = geometry.indices.len() as u32;
+
+        let quantize_mask_scale =
+            group.quantize_mask_scale && group.texture.format() == wgpu::TextureFormat::R8Unorm;
+        for item in group.items.iter() {
+            let vertex_start = geometry.vertices.len() as u32;
+            push_item(&mut geometry, item, quantize_mask_scale, options);
+            geometry
+                .items
+                .push(vertex_start..geometry.vertices.len() as u32);
+        }
+
+        geometry
+            .groups
+            .push(index_buffer_start..geometry.indices.len() as u32);
+    }
+
+    geometry
+}
+
+/// Converts a color to floating point, optionally converting its color channels from sRGB to linear.
+pub(crate) fn color_to_vec4(color: Color, srgb: bool) -> Vec4 {
+    let channel = |c: u8| {
+        let c = c as f32 / 255.0;
+        if srgb {
+            crate::texture::srgb_to_linear(c)
+        } else {
+            c
+        }
+    };
+    Vec4::new(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a as f32 / 255.0,
+    )
+}
+
+/// Appends the vertices and indices of an item.
+///
+/// Items with an empty source region or whose quad has no finite, nonzero area on the target are skipped, as they would not draw anything. Items with non-finite positions are skipped too, with a warning in debug builds.
+pub(crate) fn push_item(
+    geometry: &mut Geometry,
+    item: &Item,
+    quantize_mask_scale: bool,
+    options: &GeometryOptions,
+) {
+    if item.src_size.x == 0 || item.src_size.y == 0 {
+        return;
+    }
+
+    let transform = options.view * item.transform;
+    let transform = if quantize_mask_scale {
+        quantize_scale(transform)
+    } else {
+        transform
+    };
+
+    let to_vec4 = |tint: Color| color_to_vec4(tint, options.srgb_tints);
+
+    let tint = if options.picking {
+        Vec4::new(f32::from_bits(item.pick_id), 0.0, 0.0, 0.0)
+    } else {
+        item.tint_f32.unwrap_or_else(|| to_vec4(item.tint))
+    };
+
+    // Tints for each corner of the quad, in the order top-left, bottom-left, top-right, bottom-right.
+    let tints = item
+        .tints
+        .filter(|_| !options.picking)
+        .map(|tints| tints.map(to_vec4));
+
+    let src = item.src_rect();
+    let left = src.left() as f32;
+    let top = src.top() as f32;
+    let right = src.right() as f32;
+    let bottom = src.bottom() as f32;
+
+    let tl = [left, top];
+    let bl = [left, bottom];
+    let tr = [right, top];
+    let br = [right, bottom];
+
+    // Texture coordinates for each corner of the quad, in the order top-left, bottom-left, top-right, bottom-right.
+    let (size, tex_coords) = match item.src_rotation {
+        SrcRotation::None => (item.src_size, [tl, bl, tr, br]),
+        SrcRotation::Cw90 => (item.src_size.yx(), [tr, tl, br, bl]),
+        SrcRotation::Ccw90 => (item.src_size.yx(), [bl, br, tl, tr]),
+    };
+    let size = size.as_vec2();
+
+    let corners = if let Some(corners) = item.corners {
+        corners.map(|corner| options.view.transform_point2(corner))
+    } else {
+        [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, size.y),
+            Vec2::new(size.x, 0.0),
+            Vec2::new(size.x, size.y),
+        ]
+        .map(|corner| transform.transform_point2(corner))
+    };
+    let corners = if let Some(grid) = options.pixel_snap {
+        corners.map(|corner| (corner / grid).round() * grid)
+    } else {
+        corners
+    };
+
+    // Non-finite positions, e.g. from a broken animation, can stretch a triangle across the whole target.
+    if !(corners.iter().all(|corner| corner.is_finite()) && item.z.is_finite()) {
+        if cfg!(debug_assertions) {
+            log::warn!("spright: skipping item with non-finite position: {item:?}");
+        }
+        return;
+    }
+
+    // The area of a quad is half the cross product of its diagonals. Finite corners that are far enough apart can still overflow it to infinity.
+    let [tl, bl, tr, br] = corners;
+    let area = (br - tl).perp_dot(tr - bl);
+    if !(area != 0.0 && area.is_finite()) {
+        return;
+    }
+
+    let tex_coords = tex_coords.map(Vec2::from);
+
+    // Range of the quad's interpolation parameters covered by the revealed region. The texture coordinates of the quad form a rectangle, so the region maps to a rectangle of parameters.
+    let (uv_min, uv_max) = if let Some(reveal) = item.reveal {
+        let [tl, bl, tr, _] = tex_coords;
+        let (du, dv) = (tr - tl, bl - tl);
+        let to_uv = |p: Vec2| {
+            vec2(
+                (p - tl).dot(du) / du.length_squared(),
+                (p - tl).dot(dv) / dv.length_squared(),
+            )
+        };
+        let a = to_uv(vec2(reveal.left() as f32, reveal.top() as f32));
+        let b = to_uv(vec2(reveal.right() as f32, reveal.bottom() as f32));
+        let uv_min = a.min(b).max(Vec2::ZERO);
+        let uv_max = a.max(b).min(Vec2::ONE);
+        if uv_min.x >= uv_max.x || uv_min.y >= uv_max.y {
+            return;
+        }
+        (uv_min, uv_max)
+    } else {
+        (Vec2::ZERO, Vec2::ONE)
+    };
+
+    // Top-left, bottom-left, top-right winds counterclockwise in clip space if the quad isn't mirrored, as the y axis is flipped.
+    let ccw = (bl - tl).perp_dot(tr - tl) <= 0.0;
+    let flip = ccw != (options.front_face == wgpu::FrontFace::Ccw);
+
+    // Scrolling splits the quad into up to two pieces along each axis, each with its own offset from the quad's interpolation parameters to the texture's.
+    let scroll = (item.uv_scroll / size).fract_gl();
+    let pieces = |scroll: f32, min: f32, max: f32| {
+        [
+            (0.0, 1.0 - scroll, scroll),
+            (1.0 - scroll, 1.0, scroll - 1.0),
+        ]
+        .into_iter()
+        .map(move |(start, end, offset)| (start.max(min), end.min(max), offset))
+        .filter(|(start, end, _)| start < end)
+    };
+
+    let subdivisions = item.subdivisions.max(UVec2::ONE);
+    for (u_start, u_end, u_offset) in pieces(scroll.x, uv_min.x, uv_max.x) {
+        for (v_start, v_end, v_offset) in pieces(scroll.y, uv_min.y, uv_max.y) {
+            let offset = geometry.vertices.len() as u32;
+
+            // Vertices are generated column by column from the top-left, interpolating between the corners.
+            for x in 0..=subdivisions.x {
+                let u = u_start + (u_end - u_start) * x as f32 / subdivisions.x as f32;
+                for y in 0..=subdivisions.y {
+                    let v = v_start + (v_end - v_start) * y as f32 / subdivisions.y as f32;
+                    let bilerp = |[tl, bl, tr, br]: [Vec2; 4], u: f32, v: f32| {
+                        tl.lerp(tr, u).lerp(bl.lerp(br, u), v)
+                    };
+                    geometry.vertices.push(Vertex {
+                        position: bilerp(corners, u, v).extend(item.z).to_array(),
+                        tex_coords: bilerp(tex_coords, u + u_offset, v + v_offset).to_array(),
+                        layer: item.src_layer,
+                        tint: tints
+                            .map(|[tl, bl, tr, br]| tl.lerp(tr, u).lerp(bl.lerp(br, u), v))
+                            .unwrap_or(tint)
+                            .to_array(),
+                    });
+                }
+            }
+
+            let column = subdivisions.y + 1;
+            for x in 0..subdivisions.x {
+                for y in 0..subdivisions.y {
+                    let tl = offset + x * column + y;
+                    let tr = tl + column;
+                    let quad = [tl, tl + 1, tr, tr + 1];
+                    let indices = if flip {
+                        QUAD_INDICES_FLIPPED
+                    } else {
+                        QUAD_INDICES
+                    };
+                    geometry.indices.extend(indices.map(|i| quad[i as usize]));
+                }
+            }
+        }
+    }
+}
+
+/// How far a scale may be from a whole number to be rounded by [`quantize_scale`].
+const SCALE_QUANTIZATION_TOLERANCE: f32 = 0.125;
+
+/// Rounds the translation of a transform that only scales and translates to whole pixels, and its scale to a whole number if it's close to one.
+fn quantize_scale(transform: Affine2) -> Affine2 {
+    let m = transform.matrix2;
+    if m.x_axis.y != 0.0 || m.y_axis.x != 0.0 {
+        return transform;
+    }
+    let quantize = |scale: f32| {
+        let rounded = scale.round();
+        if rounded != 0.0 && (scale - rounded).abs() <= SCALE_QUANTIZATION_TOLERANCE {
+            rounded
+        } else {
+            scale
+        }
+    };
+    Affine2::from_scale_angle_translation(
+        vec2(quantize(m.x_axis.x), quantize(m.y_axis.y)),
+        0.0,
+        transform.translation.round(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rect;
+
+    fn item(src_size: UVec2, transform: Affine2) -> Item {
+        Item {
+            src_size,
+            transform,
+            ..Default::default()
+        }
+    }
+
+    fn push(item: &Item, options: &GeometryOptions) -> Geometry {
+        let mut geometry = Geometry::default();
+        push_item(&mut geometry, item, false, options);
+        geometry
+    }
+
+    /// Gets the position and texture coordinates of each vertex of an item.
+    fn vertices(item: &Item) -> Vec<(Vec2, Vec2)> {
+        push(item, &GeometryOptions::default())
+            .vertices
+            .iter()
+            .map(|vertex| {
+                (
+                    vec2(vertex.position[0], vertex.position[1]),
+                    Vec2::from(vertex.tex_coords),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn skips_empty_items() {
+        let options = GeometryOptions::default();
+        for item in [
+            item(uvec2(0, 16), Affine2::IDENTITY),
+            item(uvec2(16, 0), Affine2::IDENTITY),
+            item(uvec2(16, 16), Affine2::from_scale(Vec2::ZERO)),
+            item(uvec2(16, 16), Affine2::from_scale(vec2(1.0, 0.0))),
+        ] {
+            let geometry = push(&item, &options);
+            assert!(geometry.indices.is_empty(), "{item:?}");
+            assert!(geometry.vertices.is_empty(), "{item:?}");
+        }
+    }
+
+    #[test]
+    fn skips_non_finite_items() {
+        let options = GeometryOptions::default();
+        for item in [
+            item(
+                uvec2(16, 16),
+                Affine2::from_translation(vec2(f32::NAN, 0.0)),
+            ),
+            item(
+                uvec2(16, 16),
+                Affine2::from_translation(vec2(0.0, f32::INFINITY)),
+            ),
+            Item {
+                z: f32::NAN,
+                ..item(uvec2(16, 16), Affine2::IDENTITY)
+            },
+        ] {
+            let geometry = push(&item, &options);
+            assert!(geometry.indices.is_empty(), "{item:?}");
+            assert!(geometry.vertices.is_empty(), "{item:?}");
+        }
+    }
+
+    #[test]
+    fn skips_items_with_overflowing_area() {
+        let item = item(uvec2(16, 16), Affine2::from_scale(Vec2::splat(1e30)));
+        assert!(push(&item, &GeometryOptions::default()).indices.is_empty());
+    }
+
+    #[test]
+    fn winding() {
+        let quad = item(uvec2(16, 16), Affine2::IDENTITY);
+        let mirrored = item(uvec2(16, 16), Affine2::from_scale(vec2(-1.0, 1.0)));
+        for (item, front_face, indices) in [
+            (&quad, wgpu::FrontFace::Ccw, QUAD_INDICES),
+            (&quad, wgpu::FrontFace::Cw, QUAD_INDICES_FLIPPED),
+            (&mirrored, wgpu::FrontFace::Ccw, QUAD_INDICES_FLIPPED),
+            (&mirrored, wgpu::FrontFace::Cw, QUAD_INDICES),
+        ] {
+            let options = GeometryOptions {
+                front_face,
+                ..Default::default()
+            };
+            assert_eq!(push(item, &options).indices, indices, "{front_face:?}");
+        }
+    }
+
+    #[test]
+    fn front_facing_in_clip_space() {
+        for front_face in [wgpu::FrontFace::Ccw, wgpu::FrontFace::Cw] {
+            let options = GeometryOptions {
+                front_face,
+                ..Default::default()
+            };
+            for scale in [vec2(1.0, 1.0), vec2(-1.0, 1.0), vec2(1.0, -1.0)] {
+                let geometry = push(&item(uvec2(16, 16), Affine2::from_scale(scale)), &options);
+                for triangle in geometry.indices.chunks(3) {
+                    // Target pixels have y pointing down, so flip it to get clip space.
+                    let [a, b, c] = [0, 1, 2].map(|i| {
+                        let [x, y, _] = geometry.vertices[triangle[i] as usize].position;
+                        vec2(x, -y)
+                    });
+                    let ccw = (b - a).perp_dot(c - a) > 0.0;
+                    assert_eq!(ccw, front_face == wgpu::FrontFace::Ccw, "{scale}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vertex_order() {
+        let geometry = push(
+            &item(uvec2(16, 8), Affine2::from_translation(vec2(1.0, 2.0))),
+            &GeometryOptions::default(),
+        );
+        let positions = geometry
+            .vertices
+            .iter()
+            .map(|vertex| vec2(vertex.position[0], vertex.position[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [
+                vec2(1.0, 2.0),
+                vec2(1.0, 10.0),
+                vec2(17.0, 2.0),
+                vec2(17.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn src_rotation() {
+        // Vertices are top-left, bottom-left, top-right, bottom-right on the target, and the source region is 16 by 8 texels at (32, 64).
+        let (tl, bl, tr, br) = (
+            vec2(32.0, 64.0),
+            vec2(32.0, 72.0),
+            vec2(48.0, 64.0),
+            vec2(48.0, 72.0),
+        );
+        for (src_rotation, expected) in [
+            (
+                SrcRotation::None,
+                [
+                    (vec2(0.0, 0.0), tl),
+                    (vec2(0.0, 8.0), bl),
+                    (vec2(16.0, 0.0), tr),
+                    (vec2(16.0, 8.0), br),
+                ],
+            ),
+            (
+                SrcRotation::Cw90,
+                [
+                    (vec2(0.0, 0.0), tr),
+                    (vec2(0.0, 16.0), tl),
+                    (vec2(8.0, 0.0), br),
+                    (vec2(8.0, 16.0), bl),
+                ],
+            ),
+            (
+                SrcRotation::Ccw90,
+                [
+                    (vec2(0.0, 0.0), bl),
+                    (vec2(0.0, 16.0), br),
+                    (vec2(8.0, 0.0), tl),
+                    (vec2(8.0, 16.0), tr),
+                ],
+            ),
+        ] {
+            let item = Item {
+                src_offset: ivec2(32, 64),
+                src_rotation,
+                ..item(uvec2(16, 8), Affine2::IDENTITY)
+            };
+            assert_eq!(vertices(&item), expected, "{src_rotation:?}");
+        }
+    }
+
+    #[test]
+    fn reveal() {
+        let revealed = |reveal| Item {
+            src_offset: ivec2(32, 64),
+            reveal,
+            ..item(uvec2(16, 8), Affine2::IDENTITY)
+        };
+
+        // Only the columns from 36 to 44 of the source region are shown, in place.
+        assert_eq!(
+            vertices(&revealed(Some(Rect::new(ivec2(36, 0), uvec2(8, 100))))),
+            [
+                (vec2(4.0, 0.0), vec2(36.0, 64.0)),
+                (vec2(4.0, 8.0), vec2(36.0, 72.0)),
+                (vec2(12.0, 0.0), vec2(44.0, 64.0)),
+                (vec2(12.0, 8.0), vec2(44.0, 72.0)),
+            ]
+        );
+
+        // Regions outside of the source region or with no area show nothing.
+        for reveal in [
+            Rect::new(ivec2(0, 0), uvec2(32, 64)),
+            Rect::new(ivec2(40, 64), uvec2(0, 8)),
+            Rect::new(ivec2(32, 68), uvec2(16, 0)),
+        ] {
+            assert!(vertices(&revealed(Some(reveal))).is_empty(), "{reveal:?}");
+        }
+    }
+
+    #[test]
+    fn uv_scroll_wraps_around() {
+        let scrolled = |uv_scroll, reveal| Item {
+            src_offset: ivec2(32, 64),
+            uv_scroll,
+            reveal,
+            ..item(uvec2(16, 8), Affine2::IDENTITY)
+        };
+
+        // Scrolling by a quarter splits the quad where the source region wraps around, and whole turns scroll by nothing.
+        for uv_scroll in [vec2(4.0, 0.0), vec2(-12.0, 8.0), vec2(36.0, -16.0)] {
+            assert_eq!(
+                vertices(&scrolled(uv_scroll, None)),
+                [
+                    (vec2(0.0, 0.0), vec2(36.0, 64.0)),
+                    (vec2(0.0, 8.0), vec2(36.0, 72.0)),
+                    (vec2(12.0, 0.0), vec2(48.0, 64.0)),
+                    (vec2(12.0, 8.0), vec2(48.0, 72.0)),
+                    (vec2(12.0, 0.0), vec2(32.0, 64.0)),
+                    (vec2(12.0, 8.0), vec2(32.0, 72.0)),
+                    (vec2(16.0, 0.0), vec2(36.0, 64.0)),
+                    (vec2(16.0, 8.0), vec2(36.0, 72.0)),
+                ],
+                "{uv_scroll}"
+            );
+        }
+
+        // Pieces that are clipped away entirely aren't emitted.
+        assert_eq!(
+            vertices(&scrolled(
+                vec2(4.0, 0.0),
+                Some(Rect::new(ivec2(32, 64), uvec2(8, 8)))
+            )),
+            [
+                (vec2(0.0, 0.0), vec2(36.0, 64.0)),
+                (vec2(0.0, 8.0), vec2(36.0, 72.0)),
+                (vec2(8.0, 0.0), vec2(44.0, 64.0)),
+                (vec2(8.0, 8.0), vec2(44.0, 72.0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Helpers for creating a device without a window, e.g. for tests, CI and servers.
+
+/// An error creating a headless device.
+#[derive(Debug)]
+pub enum Error {
+    /// No adapter was found, not even a software fallback adapter.
+    NoAdapter,
+
+    /// An adapter was found, but creating a device from it failed.
+    RequestDevice(wgpu::RequestDeviceError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NoAdapter => write!(f, "spright: no suitable adapter found"),
+            Error::RequestDevice(e) => write!(f, "spright: failed to request device: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::NoAdapter => None,
+            Error::RequestDevice(e) => Some(e),
+        }
+    }
+}
+
+/// Requests a device that can be used with a [`crate::Renderer`] without a surface.
+///
+/// A hardware adapter is preferred, but if there is none, a software fallback adapter is used if the instance's backends provide one (e.g. WARP on DX12, or lavapipe or llvmpipe through Vulkan or GL). The device is created with limits that spright works within on every backend.
+pub async fn request_device(
+    instance: &wgpu::Instance,
+) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), Error> {
+    let adapter = match instance
+        .request_adapter(&wgpu::RequestAdapterOptions::default())
+        .await
+    {
+        Some(adapter) => adapter,
+        None => instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                force_fallback_adapter: true,
+                ..Default::default()
+            })
+            .await
+            .ok_or(Error::NoAdapter)?,
+    };
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("spright: headless_device"),
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults()
+                    .using_resolution(adapter.limits()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .map_err(Error::RequestDevice)?;
+
+    Ok((adapter, device, queue))
+}
@@ -1,18 +1,209 @@
-use encase::{DynamicUniformBuffer, ShaderSize, ShaderType, UniformBuffer};
+use encase::{DynamicUniformBuffer, ShaderSize, UniformBuffer};
 use glam::*;
+use itertools::Itertools as _;
+use wgpu::util::DeviceExt as _;
 
+pub mod arc;
+pub mod atlas;
 pub mod batch;
+mod bind_groups;
+pub mod debug_text;
+pub mod fit;
+pub mod flipbook;
+pub mod geometry;
+pub mod headless;
+pub mod nine_slice;
+pub mod path;
+pub mod pick;
+mod pipeline;
+mod profiler;
+mod rect;
+pub mod shadow;
+pub mod texture;
+pub mod tile;
+pub mod tilemap;
+#[cfg(feature = "winit")]
+pub mod winit;
+
+use bind_groups::{BindGroupKey, BindGroups};
+use pipeline::{PipelineKey, Pipelines};
+use profiler::Profiler;
+pub use rect::Rect;
 
 pub type Color = rgb::RGBA8;
 
+/// Identifies a texture.
+///
+/// IDs are only unique among textures that are alive at the same time, and may be reused after a texture is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureId(u64);
+
+impl TextureId {
+    /// Gets the ID of a texture.
+    pub fn of(texture: &wgpu::Texture) -> Self {
+        Self(hash_of(texture))
+    }
+}
+
+fn hash_of(v: &impl std::hash::Hash) -> u64 {
+    use std::hash::Hasher as _;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    v.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// How a group is blended with the render target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Straight (non-premultiplied) alpha blending.
+    ///
+    /// Sprites drawn from an [`wgpu::TextureFormat::R8Unorm`] mask have the color of the tint and an alpha of the tint's alpha multiplied by the mask.
+    #[default]
+    Alpha,
+
+    /// Premultiplied alpha blending.
+    ///
+    /// Textures and tints must be premultiplied. Sprites drawn from an [`wgpu::TextureFormat::R8Unorm`] mask have the premultiplied tint multiplied by the mask.
+    Premultiplied,
+
+    /// Additive blending, weighted by source alpha.
+    Additive,
+
+    /// Additive blending of premultiplied colors.
+    ///
+    /// Textures and tints must be premultiplied, as with [`BlendMode::Premultiplied`]. Unlike [`BlendMode::Additive`], overlapping sprites accumulate linearly in both color and alpha, which suits stacking glows on HDR targets.
+    PremultipliedAdditive,
+
+    /// No blending: sprites replace the contents of the target, including its alpha.
+    ///
+    /// This skips blending for sprites that are known to be fully opaque, such as large backgrounds.
+    Opaque,
+}
+
+impl BlendMode {
+    fn blend_state(self) -> Option<wgpu::BlendState> {
+        Some(match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::PremultipliedAdditive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            BlendMode::Opaque => return None,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+        })
+    }
+}
+
 /// Represents a group of sprites to draw from the same texture.
 #[derive(Debug, Clone)]
 pub struct Group<'a> {
     /// Texture to draw with.
+    ///
+    /// Textures are always bound as 2D arrays, so a texture with multiple layers (e.g. animation frames) can be drawn from with [`Item::src_layer`]. All layers of a texture are drawn in the same draw call.
+    ///
+    /// [`wgpu::TextureFormat::R8Unorm`] textures are drawn as masks, with the texture as alpha. As a group only has one texture, color and mask sprites are always drawn in separate groups, including when batched with [`batch::batch`].
     pub texture: &'a wgpu::Texture,
 
     /// Items in the group.
     pub items: Vec<Item>,
+
+    /// Blend mode to draw the group with.
+    ///
+    /// Mask groups are drawn with [`Options::mask_blend_mode`] instead if it's set.
+    pub blend_mode: BlendMode,
+
+    /// Fragments with a final alpha below this value are discarded.
+    ///
+    /// This is useful for alpha-tested cutout sprites. A value of 0.0 disables it.
+    pub alpha_cutoff: f32,
+
+    /// How the group interacts with the stencil buffer.
+    pub stencil: StencilMode,
+
+    /// How the group interacts with the depth buffer.
+    pub depth: DepthMode,
+
+    /// Sampler to sample the texture with, instead of the renderer's own sampler.
+    ///
+    /// This allows sharing a sampler configured elsewhere, e.g. with anisotropic filtering. It must be a filtering sampler. Comparison samplers can't be used, as textures are bound as filterable float textures rather than depth textures.
+    pub sampler: Option<&'a wgpu::Sampler>,
+
+    /// Logical size of the target for this group, instead of the actual target size.
+    ///
+    /// Sprites in the group are positioned as if the target had this size, e.g. to draw a minimap authored at a different resolution within the same pass.
+    pub target_size: Option<Vec2>,
+
+    /// Offset added to the texture coordinates of every sprite in the group, in texels.
+    ///
+    /// Animating this scrolls the texture within each sprite without touching its geometry, e.g. for flowing water. It's most useful with [`wgpu::AddressMode::Repeat`].
+    pub uv_offset: Vec2,
+
+    /// Texture to take alpha from, instead of [`Group::texture`].
+    ///
+    /// The color is taken from [`Group::texture`] and the alpha from the red channel of this texture, sampled at the same normalized coordinates and layer. This allows using color formats without alpha, e.g. some block-compressed formats, with a separate mask.
+    pub alpha_texture: Option<&'a wgpu::Texture>,
+
+    /// Snaps mask sprites to whole target pixels, rounding their scale to a whole number if it's close to one.
+    ///
+    /// This only affects groups with an [`wgpu::TextureFormat::R8Unorm`] mask texture, and items whose transforms only scale and translate. With nearest filtering, it keeps upscaled pixel-art glyphs from having uneven stem widths.
+    pub quantize_mask_scale: bool,
+
+    /// Mip level to sample the texture at, instead of selecting it automatically.
+    ///
+    /// Fractional levels blend between mip levels when the sampler's mipmap filter is linear. This is useful e.g. for deliberately blurry thumbnails.
+    pub mip_level: Option<f32>,
+
+    /// Softens the edges of mask sprites by applying a smoothstep of this width around half coverage, or 0 to use the mask as is.
+    ///
+    /// This only affects groups with an [`wgpu::TextureFormat::R8Unorm`] mask texture, and is most useful with a [`Group::sampler`] that filters linearly. It's a cheaper alternative to distance fields for small text rendered from regular coverage masks.
+    pub mask_smoothing: f32,
+
+    /// Multiplies the colors of the group by a checkerboard pattern, e.g. to show transparency behind images in viewers and editors.
+    ///
+    /// The pattern is computed per fragment from its position in the target, so a single item stretching a white texel over an area fills it with any number of cells.
+    pub checkerboard: Option<Checkerboard>,
+}
+
+impl<'a> Group<'a> {
+    /// Creates a group with default options.
+    pub fn new(texture: &'a wgpu::Texture, items: Vec<Item>) -> Self {
+        Self {
+            texture,
+            items,
+            blend_mode: BlendMode::default(),
+            alpha_cutoff: 0.0,
+            stencil: StencilMode::default(),
+            depth: DepthMode::default(),
+            sampler: None,
+            target_size: None,
+            uv_offset: Vec2::ZERO,
+            alpha_texture: None,
+            quantize_mask_scale: false,
+            mip_level: None,
+            mask_smoothing: 0.0,
+            checkerboard: None,
+        }
+    }
 }
 
 /// Represents a sprite to draw.
@@ -24,51 +215,497 @@ pub struct Item {
     /// Source size.
     pub src_size: UVec2,
 
-    /// Source layer.
+    /// Source layer, for textures with multiple array layers.
     pub src_layer: u32,
 
     /// Target transform.
     pub transform: Affine2,
 
     /// Tint.
+    ///
+    /// This defaults to opaque white, which leaves colors unchanged and draws masks in white. A default tint for many sprites, e.g. a theme color, can be set with [`batch::SpriteBuilder::tint`].
     pub tint: Color,
+
+    /// Floating point tint, used instead of [`Item::tint`] if set.
+    ///
+    /// This is not quantized to 8 bits, so components may also exceed 1.0 for HDR targets.
+    pub tint_f32: Option<Vec4>,
+
+    /// Tints for each corner, used instead of [`Item::tint`] and [`Item::tint_f32`] if set.
+    ///
+    /// Corners are in the order top-left, bottom-left, top-right, bottom-right of the source region, and tints are interpolated between them, e.g. for gradients and vignettes.
+    pub tints: Option<[Color; 4]>,
+
+    /// ID written instead of a color when rendering with [`Options::picking`].
+    ///
+    /// 0 is conventionally used for "nothing", as pick targets are usually cleared to 0.
+    pub pick_id: u32,
+
+    /// Depth of the sprite in clip space.
+    ///
+    /// This is written to the depth of the vertices, for use with depth testing. It must be within 0.0 to 1.0, or the sprite will be clipped.
+    pub z: f32,
+
+    /// Rotation of the source region in the texture.
+    ///
+    /// Texture packers may store entries rotated by 90 degrees to pack them more tightly. [`Item::src_offset`] and [`Item::src_size`] describe the region as stored in the texture, and the sprite is drawn unrotated with the source width and height swapped, without affecting [`Item::transform`].
+    pub src_rotation: SrcRotation,
+
+    /// Target corners, used instead of transforming the source region by [`Item::transform`] if set.
+    ///
+    /// Corners are in the order top-left, bottom-left, top-right, bottom-right of the source region, and may form any quadrilateral, e.g. a triangle if two corners are equal. Texture coordinates are interpolated across each of the two triangles separately.
+    pub corners: Option<[Vec2; 4]>,
+
+    /// Number of columns and rows the quad is divided into.
+    ///
+    /// Subdividing the quad generates more vertices for effects that displace them, e.g. with [`Renderer::prepare_with`]. Components of 0 are treated as 1.
+    pub subdivisions: UVec2,
+
+    /// Part of the source region to show, in the same texel coordinates as [`Item::src_offset`], or `None` to show all of it.
+    ///
+    /// The rest of the sprite is clipped away without moving the part that is shown, e.g. for wipe transitions that reveal an image. Clipping is done on the generated geometry rather than in the shader, so it costs nothing per fragment.
+    pub reveal: Option<Rect>,
+
+    /// Scrolls the texture within the sprite by this many texels, wrapping around at the edges of the source region, e.g. for parallax layers and conveyor belts.
+    ///
+    /// The sprite itself stays in place. Wrapping is done by splitting the quad where the source region wraps around, so it doesn't need a repeating sampler and works with regions of an atlas. The offset is along the source region as drawn, i.e. after [`Item::src_rotation`].
+    pub uv_scroll: Vec2,
+}
+
+/// Rotation of a source region in a texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SrcRotation {
+    /// The source region is not rotated.
+    #[default]
+    None,
+
+    /// The source region is stored rotated 90 degrees clockwise.
+    Cw90,
+
+    /// The source region is stored rotated 90 degrees counterclockwise.
+    Ccw90,
+}
+
+impl Item {
+    /// Gets the source region as a [`Rect`].
+    pub fn src_rect(&self) -> Rect {
+        Rect {
+            offset: self.src_offset,
+            size: self.src_size,
+        }
+    }
+}
+
+impl Default for Item {
+    fn default() -> Self {
+        Self {
+            src_offset: IVec2::ZERO,
+            src_size: UVec2::ZERO,
+            src_layer: 0,
+            transform: Affine2::IDENTITY,
+            tint: Color::new(0xff, 0xff, 0xff, 0xff),
+            tint_f32: None,
+            tints: None,
+            pick_id: 0,
+            z: 0.0,
+            src_rotation: SrcRotation::None,
+            corners: None,
+            subdivisions: UVec2::ONE,
+            reveal: None,
+            uv_scroll: Vec2::ZERO,
+        }
+    }
+}
+
+/// How a group interacts with the stencil buffer.
+///
+/// Using the stencil buffer requires [`Options::depth_stencil_format`] to be set to a format with a stencil aspect, and the render pass to have a matching depth-stencil attachment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StencilMode {
+    /// The stencil buffer is not used.
+    #[default]
+    Disabled,
+
+    /// Writes the reference value into the stencil buffer wherever the group is drawn, without writing any color.
+    ///
+    /// This is used to draw mask geometry. Combine it with [`Group::alpha_cutoff`] to mask by the shape of a texture rather than the whole quad.
+    Write(u32),
+
+    /// Only draws where the stencil buffer equals the reference value.
+    Test(u32),
+}
+
+impl StencilMode {
+    fn reference(self) -> u32 {
+        match self {
+            StencilMode::Disabled => 0,
+            StencilMode::Write(reference) | StencilMode::Test(reference) => reference,
+        }
+    }
+
+    /// Gets the mode with the reference value removed, as the reference value is not part of the pipeline.
+    fn without_reference(self) -> Self {
+        match self {
+            StencilMode::Disabled => StencilMode::Disabled,
+            StencilMode::Write(_) => StencilMode::Write(0),
+            StencilMode::Test(_) => StencilMode::Test(0),
+        }
+    }
+
+    fn face_state(self) -> wgpu::StencilFaceState {
+        match self {
+            StencilMode::Disabled => wgpu::StencilFaceState::IGNORE,
+            StencilMode::Write(_) => wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Always,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: wgpu::StencilOperation::Replace,
+            },
+            StencilMode::Test(_) => wgpu::StencilFaceState {
+                compare: wgpu::CompareFunction::Equal,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op: wgpu::StencilOperation::Keep,
+            },
+        }
+    }
+}
+
+/// How a group interacts with the depth buffer.
+///
+/// Using the depth buffer requires [`Options::depth_stencil_format`] to be set to a format with a depth aspect, and the render pass to have a matching depth-stencil attachment. Depths are taken from [`Item::z`], with lower values being closer.
+///
+/// A depth prepass can reduce overdraw in scenes where large opaque sprites cover many others: draw the opaque sprites first with [`DepthMode::Prepass`], then draw the whole scene with [`DepthMode::Test`]. It only helps with heavy opaque overdraw, as every opaque sprite is drawn twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DepthMode {
+    /// The depth buffer is not used.
+    #[default]
+    Disabled,
+
+    /// Writes depth wherever the group is drawn, without writing any color.
+    ///
+    /// Combine it with [`Group::alpha_cutoff`] for sprites that aren't opaque everywhere.
+    Prepass,
+
+    /// Only draws where the sprite is at least as close as the depth buffer, without writing depth.
+    Test,
+}
+
+impl DepthMode {
+    fn depth_write_enabled(self) -> bool {
+        self == DepthMode::Prepass
+    }
+
+    fn depth_compare(self) -> wgpu::CompareFunction {
+        match self {
+            DepthMode::Disabled => wgpu::CompareFunction::Always,
+            DepthMode::Prepass => wgpu::CompareFunction::Less,
+            DepthMode::Test => wgpu::CompareFunction::LessEqual,
+        }
+    }
+}
+
+/// Options for the sampler used to sample textures.
+#[derive(Debug, Clone)]
+pub struct SamplerOptions {
+    /// How to address texels outside of the texture.
+    ///
+    /// [`wgpu::AddressMode::ClampToBorder`] requires [`wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER`], which is not available on the web.
+    pub address_mode: wgpu::AddressMode,
+
+    /// Border color to use with [`wgpu::AddressMode::ClampToBorder`].
+    ///
+    /// [`wgpu::SamplerBorderColor::Zero`] additionally requires [`wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO`]. If unset, transparent black is used.
+    pub border_color: Option<wgpu::SamplerBorderColor>,
+
+    /// Filter to use when magnifying.
+    pub mag_filter: wgpu::FilterMode,
+
+    /// Filter to use when minifying.
+    pub min_filter: wgpu::FilterMode,
+
+    /// Filter to use between mip levels.
+    pub mipmap_filter: wgpu::FilterMode,
+
+    /// Minimum level of detail to sample.
+    pub lod_min_clamp: f32,
+
+    /// Maximum level of detail to sample.
+    pub lod_max_clamp: f32,
+
+    /// Bias added to the level of detail before sampling.
+    ///
+    /// Positive values blur and negative values sharpen.
+    ///
+    /// The level of detail options only take effect with textures that have mip levels, and are most useful with a [`wgpu::FilterMode::Linear`] mipmap filter.
+    pub lod_bias: f32,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            address_mode: wgpu::AddressMode::ClampToEdge,
+            border_color: None,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            lod_bias: 0.0,
+        }
+    }
+}
+
+/// A background drawn behind all groups, see [`Renderer::set_background`].
+#[derive(Debug, Clone, Copy)]
+pub enum Background<'a> {
+    /// A solid color.
+    Color(Color),
+
+    /// The first layer of a texture, stretched to the target.
+    Texture(&'a wgpu::Texture),
+
+    /// The first layer of a texture, fitted to the target with a [`fit::FitMode`], e.g. to cover the target without distorting the image.
+    ///
+    /// The fit is computed from the target size in every [`Renderer::prepare`], so it adapts to resizes. Areas left uncovered by [`fit::FitMode::Contain`] are not drawn.
+    FittedTexture(&'a wgpu::Texture, fit::FitMode),
+}
+
+/// A darkening of the edges of the target, see [`Renderer::set_vignette`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vignette {
+    /// How much the corners are darkened, from 0 for not at all to 1 for black.
+    pub strength: f32,
+
+    /// Distance from the center at which darkening starts, from 0 at the center to 1 at the corners.
+    pub radius: f32,
+}
+
+/// A checkerboard pattern, see [`Group::checkerboard`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkerboard {
+    /// Size of each cell in target pixels.
+    pub cell_size: f32,
+
+    /// Colors of the cells, starting with the cell at the top-left of the target.
+    pub colors: [Color; 2],
+}
+
+struct PreparedBackground {
+    view: wgpu::TextureView,
+    /// ID of the background texture, if it was provided by the user.
+    texture: Option<TextureId>,
+    size: UVec2,
+    tint: Color,
+    fit: fit::FitMode,
+}
+
+/// Options for creating a [`Renderer`].
+#[derive(Debug, Clone, Default)]
+pub struct Options<'a> {
+    /// Sampler options.
+    pub sampler: SamplerOptions,
+
+    /// Format of the depth-stencil attachment of render passes the renderer will render into, if any.
+    ///
+    /// This must be set to use [`StencilMode`].
+    pub depth_stencil_format: Option<wgpu::TextureFormat>,
+
+    /// Layout of the user bind group at slot 2.
+    ///
+    /// If set, the bind group must be set on the render pass before calling [`Renderer::render`]. Otherwise, an empty bind group is used.
+    pub user_bind_group_layout: Option<&'a wgpu::BindGroupLayout>,
+
+    /// Ignores item tints and omits the tint from the vertex buffer.
+    ///
+    /// This shrinks each vertex from 40 to 24 bytes, for scenes that don't use tinting.
+    pub disable_tint: bool,
+
+    /// Disables blending for all groups, as if every group used [`BlendMode::Opaque`].
+    ///
+    /// This suits scenes that never use transparency, such as solid tilemaps. Alpha blending remains the default.
+    pub disable_blending: bool,
+
+    /// Treats [`Color`] tints as sRGB, converting them to linear before they are multiplied with texture colors.
+    ///
+    /// Tints are multiplied with colors in the space the shader outputs. For sRGB target formats, this is linear, as the output is encoded to sRGB when written. For other formats, the output is written as is, so tints are in whatever space the target is in. If the target holds linear colors but tints are authored in sRGB (e.g. picked from a color picker), this converts them to match. Alpha and [`Item::tint_f32`] are never converted.
+    pub srgb_tints: bool,
+
+    /// Keeps texture bind groups alive across frames instead of recreating them in every [`Renderer::prepare`].
+    ///
+    /// Bind groups for textures that weren't drawn in a frame are dropped at the end of the next [`Renderer::prepare`]. If a texture that was drawn in the last frame is dropped and another texture is created before the next frame, the new texture may get the old texture's [`TextureId`], and [`Renderer::invalidate_texture`] must be called to avoid drawing the old texture instead.
+    pub cache_bind_groups: bool,
+
+    /// Renders [`Item::pick_id`]s instead of colors, for pixel-perfect picking.
+    ///
+    /// The texture format must be [`wgpu::TextureFormat::R32Uint`]. Fragments where the sprite's texture is transparent are not written, so overlapping and rotated sprites are picked by their actual shape. See [`pick::read_pick_id`] for reading the result back.
+    pub picking: bool,
+
+    /// Measures how long the GPU takes to draw each group, see [`Renderer::resolve_profile`].
+    ///
+    /// This requires [`wgpu::Features::TIMESTAMP_QUERY`] and [`wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES`], and does nothing if the device doesn't have them enabled.
+    pub profiling: bool,
+
+    /// Winding order of front-facing triangles.
+    ///
+    /// Triangles are always emitted with this winding on screen, so sprites are front-facing even if their transform mirrors them.
+    pub front_face: wgpu::FrontFace,
+
+    /// Faces to cull, e.g. to match the render state conventions of a surrounding 3D renderer.
+    pub cull_mode: Option<wgpu::Face>,
+
+    /// Maximum number of indices drawn by a single draw call, to work around backends that struggle with very large draws.
+    ///
+    /// Groups with more indices are split into multiple draw calls. This is rounded down to whole triangles. If not set, each group is drawn with one draw call.
+    pub max_indices_per_draw: Option<u32>,
+
+    /// Format of the index buffer.
+    ///
+    /// [`wgpu::IndexFormat::Uint16`] halves the size of the index buffer, but limits each [`Renderer::prepare`] to 65536 vertices in total, as indices are absolute rather than relative to each group.
+    pub index_format: wgpu::IndexFormat,
+
+    /// Blend mode to draw mask groups with, used instead of their [`Group::blend_mode`] if set.
+    ///
+    /// Mask groups are groups with [`wgpu::TextureFormat::R8Unorm`] textures, e.g. text, so this sets how text is composited independently of the blend modes of color sprites.
+    pub mask_blend_mode: Option<BlendMode>,
+}
+
+/// GPU memory used by a [`Renderer`]'s buffers, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Size of the vertex buffer.
+    pub vertex_buffer: u64,
+
+    /// Size of the index buffer.
+    pub index_buffer: u64,
+
+    /// Size of the uniform buffers.
+    pub uniform_buffers: u64,
+}
+
+impl MemoryUsage {
+    /// Gets the total size of all buffers.
+    pub fn total(&self) -> u64 {
+        self.vertex_buffer + self.index_buffer + self.uniform_buffers
+    }
 }
 
 /// Encapsulates static state for rendering.
+///
+/// Pipelines use the following bind group slots:
+///
+/// - 0: Per-group texture, sampler and texture uniforms.
+/// - 1: Per-frame target uniforms.
+/// - 2: Reserved for user globals, see [`Options::user_bind_group_layout`].
 pub struct Renderer {
-    render_pipeline: wgpu::RenderPipeline,
+    pipelines: Pipelines,
     texture_bind_group_layout: wgpu::BindGroupLayout,
     target_uniforms_buffer: wgpu::Buffer,
     target_uniforms_bind_group: wgpu::BindGroup,
+    /// Contents of the target uniforms buffer, to skip redundant writes.
+    last_target_uniforms: Vec<u8>,
+    /// Contents of the texture uniforms buffer, to skip redundant writes.
+    last_texture_uniforms: Vec<u8>,
+    /// Empty bind group for slot 2, if no user bind group layout was provided.
+    empty_user_bind_group: Option<wgpu::BindGroup>,
     texture_uniforms_buffer: DynamicBuffer,
     prepared_groups: Vec<PreparedGroup>,
     vertex_buffer: DynamicBuffer,
     index_buffer: DynamicBuffer,
     sampler: wgpu::Sampler,
+    bind_groups: BindGroups,
+    scale_factor: f32,
+    pixel_snap: Option<f32>,
+    max_indices_per_draw: Option<u32>,
+    index_format: wgpu::IndexFormat,
+    mask_blend_mode: Option<BlendMode>,
+    srgb_tints: bool,
+    global_tint: Color,
+    dither: bool,
+    vignette: Option<Vignette>,
+    projection: Option<Mat4>,
+    coverage_output: bool,
+    texture_format: wgpu::TextureFormat,
+    /// Target format the last [`Renderer::prepare`] prepared sprites for.
+    prepared_texture_format: wgpu::TextureFormat,
+    background: Option<PreparedBackground>,
+    background_bind_group: Option<wgpu::BindGroup>,
+    /// ID of the user texture of the background drawn by the last [`Renderer::prepare`], if any.
+    prepared_background_texture: Option<TextureId>,
+    /// Vertex ranges of the items prepared by the last [`Renderer::prepare`], for [`Renderer::update_tint`].
+    prepared_items: Vec<std::ops::Range<u32>>,
+    /// Index into [`Renderer::prepared_items`] of the first item of each prepared group.
+    prepared_group_items: Vec<usize>,
+    profiler: Option<Profiler>,
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: [f32; 3],
-    tex_coords: [f32; 2],
-    layer: u32,
-    tint: [f32; 4],
-}
+// The `ShaderType` and `Pod` derives emit helper items that newer compilers flag as dead code. Those
+// don't inherit attributes from the struct they are derived for, so the allowance covers this module.
+#[allow(dead_code)]
+mod gpu_types {
+    use encase::ShaderType;
+    use glam::*;
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, ShaderType)]
-struct TextureUniforms {
-    size: Vec3,
-    is_mask: u32,
-}
+    /// A vertex generated by [`Renderer::prepare`](crate::Renderer::prepare), as passed to [`Renderer::prepare_with`](crate::Renderer::prepare_with).
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+    pub struct Vertex {
+        /// Position in target pixels, and depth in clip space.
+        pub position: [f32; 3],
+
+        /// Texture coordinates in texels.
+        pub tex_coords: [f32; 2],
+
+        /// Texture layer.
+        pub layer: u32,
+
+        /// Tint.
+        ///
+        /// When rendering with [`Options::picking`](crate::Options::picking), the first component holds the bits of the pick ID instead.
+        pub tint: [f32; 4],
+    }
+
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, ShaderType)]
+    pub(crate) struct TextureUniforms {
+        pub(crate) size: Vec3,
+        pub(crate) is_mask: u32,
+        pub(crate) premultiplied: u32,
+        pub(crate) has_alpha_texture: u32,
+        pub(crate) alpha_cutoff: f32,
+        pub(crate) target_size: Vec2,
+        pub(crate) uv_offset: Vec2,
+        pub(crate) mip_level: f32,
+        pub(crate) mask_smoothing: f32,
+        pub(crate) checkerboard_cell_size: f32,
+        pub(crate) checkerboard_colors: [Vec4; 2],
+    }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, ShaderType)]
-struct TargetUniforms {
-    size: Vec3,
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, ShaderType)]
+    pub(crate) struct TargetUniforms {
+        pub(crate) size: Vec3,
+        pub(crate) has_projection: u32,
+        pub(crate) projection: Mat4,
+        pub(crate) tint: Vec4,
+        pub(crate) dither: u32,
+        pub(crate) vignette_strength: f32,
+        pub(crate) vignette_radius: f32,
+    }
+
+    /// A [`Vertex`] without a tint, used with [`Options::disable_tint`](crate::Options::disable_tint).
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+    pub(crate) struct UntintedVertex {
+        pub(crate) position: [f32; 3],
+        pub(crate) tex_coords: [f32; 2],
+        pub(crate) layer: u32,
+    }
 }
 
+pub use gpu_types::Vertex;
+use gpu_types::{TargetUniforms, TextureUniforms, UntintedVertex};
+
 impl Vertex {
     const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
         array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
@@ -77,6 +714,33 @@ impl Vertex {
     };
 }
 
+impl UntintedVertex {
+    const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2=> Uint32],
+    };
+}
+
+impl Vertex {
+    /// Buffer layout used with [`Options::picking`], where the tint holds the pick ID in its first component.
+    const PICKING_BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2=> Uint32, 3 => Uint32x4],
+    };
+}
+
+impl From<Vertex> for UntintedVertex {
+    fn from(vertex: Vertex) -> Self {
+        Self {
+            position: vertex.position,
+            tex_coords: vertex.tex_coords,
+            layer: vertex.layer,
+        }
+    }
+}
+
 struct DynamicBuffer {
     inner: wgpu::Buffer,
     label: Option<String>,
@@ -92,7 +756,7 @@ impl DynamicBuffer {
 
     fn reallocate(&mut self, device: &wgpu::Device, size: wgpu::BufferAddress) -> wgpu::Buffer {
         let mut old = device.create_buffer(&wgpu::BufferDescriptor {
-            label: self.label.as_ref().map(|v| v.as_str()),
+            label: self.label.as_deref(),
             size,
             usage: self.inner.usage(),
             mapped_at_creation: true,
@@ -101,7 +765,8 @@ impl DynamicBuffer {
         old
     }
 
-    fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) {
+    /// Writes data to the buffer, returning whether the buffer was reallocated.
+    fn write(&mut self, device: &wgpu::Device, uploader: &mut Uploader<'_>, data: &[u8]) -> bool {
         let size = data.len() as u64;
         if self.inner.size() < size {
             self.reallocate(device, size);
@@ -110,8 +775,84 @@ impl DynamicBuffer {
                 view.copy_from_slice(data);
             }
             self.inner.unmap();
+            true
         } else {
-            queue.write_buffer(&self.inner, 0, data);
+            uploader.write_buffer(device, &self.inner, data);
+            false
+        }
+    }
+}
+
+fn prepare_background_texture(texture: &wgpu::Texture, fit: fit::FitMode) -> PreparedBackground {
+    PreparedBackground {
+        view: texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        }),
+        texture: Some(TextureId::of(texture)),
+        size: uvec2(texture.width(), texture.height()),
+        tint: Color::new(0xff, 0xff, 0xff, 0xff),
+        fit,
+    }
+}
+
+fn create_texture_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture_uniforms_buffer: &wgpu::Buffer,
+    view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+    alpha_view: Option<&wgpu::TextureView>,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("spright: texture_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: texture_uniforms_buffer,
+                    offset: 0,
+                    size: Some(TextureUniforms::SHADER_SIZE),
+                }),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(alpha_view.unwrap_or(view)),
+            },
+        ],
+    })
+}
+
+/// Where buffer uploads are recorded.
+enum Uploader<'a> {
+    Queue(&'a wgpu::Queue),
+    Encoder(&'a mut wgpu::CommandEncoder),
+}
+
+impl Uploader<'_> {
+    fn write_buffer(&mut self, device: &wgpu::Device, buffer: &wgpu::Buffer, data: &[u8]) {
+        match self {
+            Uploader::Queue(queue) => queue.write_buffer(buffer, 0, data),
+            Uploader::Encoder(encoder) => {
+                if data.is_empty() {
+                    return;
+                }
+                let staging_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("spright: staging_buffer"),
+                    contents: data,
+                    usage: wgpu::BufferUsages::COPY_SRC,
+                });
+                encoder.copy_buffer_to_buffer(&staging_buffer, 0, buffer, 0, data.len() as u64);
+            }
         }
     }
 }
@@ -125,46 +866,110 @@ impl std::ops::Deref for DynamicBuffer {
 }
 
 struct PreparedGroup {
-    texture_bind_group: wgpu::BindGroup,
+    pipeline_key: PipelineKey,
+    stencil_reference: u32,
+    /// Key of the group's bind group, or `None` for the background.
+    bind_group_key: Option<BindGroupKey>,
+    texture_uniforms_offset: u32,
+    /// Start of the group's indices, which are absolute so that groups are drawn with a base vertex of 0, as WebGL2 does not support non-zero base vertices.
     index_buffer_start: u32,
     index_buffer_end: u32,
 }
 
 impl Renderer {
+    /// Entries of the layout of the per-group texture bind group at slot 0.
+    ///
+    /// A layout created elsewhere with these entries is compatible with the renderer's own, e.g. for pipelines shared with other renderers. See also [`Renderer::texture_bind_group_layout`].
+    pub const TEXTURE_BIND_GROUP_LAYOUT_ENTRIES: &'static [wgpu::BindGroupLayoutEntry] = &[
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2Array,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2Array,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+    ];
+
     /// Creates a new renderer.
     pub fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        Self::with_options(device, texture_format, &Options::default())
+    }
+
+    /// Creates a new renderer for a configured surface, using the surface's format.
+    ///
+    /// If the surface is later reconfigured with a different format, pass the new format to [`Renderer::set_texture_format`].
+    pub fn for_surface(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        Self::new(device, config.format)
+    }
+
+    /// Creates a new renderer with the given options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the options require features that the device does not have enabled.
+    pub fn with_options(
+        device: &wgpu::Device,
+        texture_format: wgpu::TextureFormat,
+        options: &Options<'_>,
+    ) -> Self {
+        let border_color = if options.sampler.address_mode == wgpu::AddressMode::ClampToBorder {
+            assert!(
+                device
+                    .features()
+                    .contains(wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER),
+                "spright: ClampToBorder requires Features::ADDRESS_MODE_CLAMP_TO_BORDER"
+            );
+            let border_color = options
+                .sampler
+                .border_color
+                .unwrap_or(wgpu::SamplerBorderColor::TransparentBlack);
+            if border_color == wgpu::SamplerBorderColor::Zero {
+                assert!(
+                    device
+                        .features()
+                        .contains(wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO),
+                    "spright: SamplerBorderColor::Zero requires Features::ADDRESS_MODE_CLAMP_TO_ZERO"
+                );
+            }
+            Some(border_color)
+        } else {
+            None
+        };
+
         let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
         let texture_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("spright: texture_bind_group_layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2Array,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 2,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    },
-                ],
+                entries: Self::TEXTURE_BIND_GROUP_LAYOUT_ENTRIES,
             });
 
         let target_uniforms_bind_group_layout =
@@ -172,7 +977,7 @@ impl Renderer {
                 label: Some("spright: target_uniforms_bind_group_layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -183,7 +988,7 @@ impl Renderer {
             });
 
         let texture_uniforms_buffer = DynamicBuffer::new(
-            &device,
+            device,
             &wgpu::BufferDescriptor {
                 label: Some("spright: texture_uniforms_buffer"),
                 size: TextureUniforms::SHADER_SIZE.into(),
@@ -209,7 +1014,7 @@ impl Renderer {
         });
 
         let vertex_buffer = DynamicBuffer::new(
-            &device,
+            device,
             &wgpu::BufferDescriptor {
                 label: Some("spright: vertex_buffer"),
                 size: std::mem::size_of::<Vertex>() as u64 * 1024,
@@ -219,7 +1024,7 @@ impl Renderer {
         );
 
         let index_buffer = DynamicBuffer::new(
-            &device,
+            device,
             &wgpu::BufferDescriptor {
                 label: Some("spright: vertex_buffer"),
                 size: std::mem::size_of::<u32>() as u64 * 1024,
@@ -228,60 +1033,400 @@ impl Renderer {
             },
         );
 
+        let empty_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("spright: empty_bind_group_layout"),
+                entries: &[],
+            });
+
+        let empty_user_bind_group = if options.user_bind_group_layout.is_none() {
+            Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("spright: empty_user_bind_group"),
+                layout: &empty_bind_group_layout,
+                entries: &[],
+            }))
+        } else {
+            None
+        };
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("spright: render_pipeline.layout"),
+            bind_group_layouts: &[
+                &texture_bind_group_layout,
+                &target_uniforms_bind_group_layout,
+                options
+                    .user_bind_group_layout
+                    .unwrap_or(&empty_bind_group_layout),
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let mut pipelines = Pipelines::new(shader, pipeline_layout, options);
+        pipelines.ensure(
+            device,
+            PipelineKey {
+                blend_mode: BlendMode::default(),
+                stencil: StencilMode::default(),
+                depth: DepthMode::default(),
+                texture_format,
+                coverage: false,
+            },
+        );
+
         Self {
-            render_pipeline: device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("spright: render_pipeline"),
-                cache: None,
-                layout: Some(
-                    &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                        label: Some("spright: render_pipeline.layout"),
-                        bind_group_layouts: &[
-                            &texture_bind_group_layout,
-                            &target_uniforms_bind_group_layout,
-                        ],
-                        push_constant_ranges: &[],
-                    }),
-                ),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[Vertex::BUFFER_LAYOUT],
-                    compilation_options: Default::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: Some("fs_main"),
-                    compilation_options: Default::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: texture_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::all(),
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
-                multiview: None,
-            }),
+            pipelines,
             texture_bind_group_layout,
             target_uniforms_buffer,
             target_uniforms_bind_group,
+            last_target_uniforms: vec![],
+            last_texture_uniforms: vec![],
+            empty_user_bind_group,
             texture_uniforms_buffer,
             vertex_buffer,
             index_buffer,
             prepared_groups: vec![],
             sampler: device.create_sampler(&wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Nearest,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
+                address_mode_u: options.sampler.address_mode,
+                address_mode_v: options.sampler.address_mode,
+                address_mode_w: options.sampler.address_mode,
+                mag_filter: options.sampler.mag_filter,
+                min_filter: options.sampler.min_filter,
+                mipmap_filter: options.sampler.mipmap_filter,
+                lod_min_clamp: options.sampler.lod_min_clamp,
+                lod_max_clamp: options.sampler.lod_max_clamp,
+                border_color,
                 ..Default::default()
             }),
+            bind_groups: BindGroups::new(options.cache_bind_groups),
+            scale_factor: 1.0,
+            pixel_snap: None,
+            max_indices_per_draw: options.max_indices_per_draw,
+            index_format: options.index_format,
+            mask_blend_mode: options.mask_blend_mode,
+            srgb_tints: options.srgb_tints,
+            global_tint: Color::new(0xff, 0xff, 0xff, 0xff),
+            dither: false,
+            vignette: None,
+            projection: None,
+            coverage_output: false,
+            texture_format,
+            prepared_texture_format: texture_format,
+            background: None,
+            background_bind_group: None,
+            prepared_background_texture: None,
+            prepared_items: vec![],
+            prepared_group_items: vec![],
+            profiler: (options.profiling && device.features().contains(Profiler::FEATURES))
+                .then(|| Profiler::new(device)),
         }
     }
 
+    /// Drops all cached pipelines and bind groups, e.g. to reclaim memory after a level that used many blend and stencil modes.
+    ///
+    /// Pipelines are cached for every combination of modes and formats drawn so far and are never dropped otherwise, whereas bind groups are already dropped once their textures stop being drawn. Both are recreated on demand, which may cause a hitch in the next [`Renderer::prepare`] much like the first time they were created. This also drops the prepared sprites, so nothing is drawn until sprites are prepared again.
+    pub fn clear_caches(&mut self) {
+        self.pipelines.clear();
+        self.bind_groups.clear();
+        self.prepared_groups.clear();
+        self.background_bind_group = None;
+        self.prepared_background_texture = None;
+    }
+
+    /// Drops any cached bind groups for a texture.
+    ///
+    /// This only needs to be called when using [`Options::cache_bind_groups`], before a texture drawn in the last frame is dropped.
+    pub fn invalidate_texture(&mut self, texture: TextureId) {
+        self.bind_groups.invalidate_texture(texture);
+    }
+
+    /// Sets the scale factor that sprite positions and sizes are multiplied by.
+    ///
+    /// This allows sprites to be authored in logical pixels while the target size passed to [`Renderer::prepare`] remains in physical pixels. The scale is applied on top of each item's transform.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Sets a grid in target pixels that the corners of sprites are rounded to in following calls to [`Renderer::prepare`], or disables snapping.
+    ///
+    /// Corners are snapped after all transforms and the scale factor are applied, so pixel art drawn through a camera with a fractional zoom stays crisp instead of shimmering as it moves. Passing the whole part of the zoom keeps every source texel the same size on screen. Sprites may grow or shrink by up to half a grid cell on each side, and the background is never snapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid size is not positive.
+    pub fn set_pixel_snap(&mut self, grid: Option<f32>) {
+        assert!(
+            grid.is_none_or(|grid| grid > 0.0),
+            "spright: pixel snap grid must be positive"
+        );
+        self.pixel_snap = grid;
+    }
+
+    /// Sets a tint that is multiplied into the color of everything drawn by following calls to [`Renderer::prepare`], including the background.
+    ///
+    /// This is useful for screen-wide effects such as flashes and fades. The default of opaque white has no effect. The global tint is not applied when rendering with [`Options::picking`].
+    pub fn set_global_tint(&mut self, tint: Color) {
+        self.global_tint = tint;
+    }
+
+    /// Gets the current global tint.
+    pub fn global_tint(&self) -> Color {
+        self.global_tint
+    }
+
+    /// Enables or disables ordered dithering of everything drawn by following calls to [`Renderer::prepare`].
+    ///
+    /// Dithering adds a small screen-space pattern of up to half a step of an 8-bit channel to each color, which breaks up banding in gradients and fades. It's disabled by default, as it adds noise.
+    pub fn set_dither(&mut self, dither: bool) {
+        self.dither = dither;
+    }
+
+    /// Sets a vignette that darkens the edges of everything drawn by following calls to [`Renderer::prepare`], or removes it.
+    ///
+    /// The vignette is applied to each sprite as it is drawn rather than to the finished target, so areas that no sprite covers are not darkened. Use [`Renderer::set_background`] instead of clearing to have the whole target darkened.
+    pub fn set_vignette(&mut self, vignette: Option<Vignette>) {
+        self.vignette = vignette;
+    }
+
+    /// Sets a background that is drawn behind all groups in following calls to [`Renderer::prepare`], or removes it.
+    ///
+    /// The background covers the whole target unless it's fitted with [`fit::FitMode::Contain`], and is drawn with alpha blending. Clearing the render pass remains an alternative for solid colors.
+    pub fn set_background(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        background: Option<Background<'_>>,
+    ) {
+        self.background = background.map(|background| match background {
+            Background::Color(color) => {
+                let texture = texture::create_layered_texture(
+                    device,
+                    queue,
+                    "spright: background_texture",
+                    UVec2::ONE,
+                    wgpu::TextureFormat::Rgba8Unorm,
+                    &[0xff; 4],
+                );
+                PreparedBackground {
+                    view: texture.create_view(&wgpu::TextureViewDescriptor {
+                        dimension: Some(wgpu::TextureViewDimension::D2Array),
+                        ..Default::default()
+                    }),
+                    texture: None,
+                    size: UVec2::ONE,
+                    tint: color,
+                    fit: fit::FitMode::Fill,
+                }
+            }
+            Background::Texture(texture) => prepare_background_texture(texture, fit::FitMode::Fill),
+            Background::FittedTexture(texture, fit) => prepare_background_texture(texture, fit),
+        });
+    }
+
+    /// Replaces the built-in shader with new WGSL source, e.g. to hot-reload a modified copy of it during development.
+    ///
+    /// All pipelines are recreated with the new shader, while buffers, layouts and bind groups are kept. The source must have the same entry points and bindings as the built-in shader. If it fails to compile or the pipelines fail to validate, the error is returned and the previous shader and pipelines are kept.
+    pub async fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Result<(), wgpu::Error> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("spright: shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let previous = self.pipelines.replace_shader(device, shader);
+        if let Some(error) = device.pop_error_scope().await {
+            self.pipelines.restore(previous);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Gets the layout of the per-group texture bind group at slot 0, e.g. to create pipelines shared with other renderers.
+    pub fn texture_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.texture_bind_group_layout
+    }
+
+    /// Gets the matrix that maps item coordinates to clip space for a target of the given size, e.g. to align other draws with sprites.
+    ///
+    /// Item coordinates are multiplied by the scale factor, normalized by the target size, and have their y axis flipped so that y points down. Depths are passed through unchanged. Groups with [`Group::target_size`] set use that size instead. If a projection was set with [`Renderer::set_projection`], it is used instead of normalizing by the target size.
+    pub fn projection_matrix(&self, target_size: wgpu::Extent3d) -> Mat4 {
+        let scale = Mat4::from_scale(Vec3::new(self.scale_factor, self.scale_factor, 1.0));
+        if let Some(projection) = self.projection {
+            return projection * scale;
+        }
+        let size = vec2(target_size.width as f32, target_size.height as f32);
+        Mat4::from_translation(vec3(-1.0, 1.0, 0.0))
+            * Mat4::from_scale((vec2(2.0, -2.0) / size).extend(1.0))
+            * scale
+    }
+
+    /// Sets a projection that maps positions in target pixels, after the scale factor is applied, to clip space in following calls to [`Renderer::prepare`], or removes it.
+    ///
+    /// This replaces the default mapping of the target size to clip space, e.g. to share a camera with a surrounding renderer. [`Group::target_size`] has no effect while a projection is set.
+    pub fn set_projection(&mut self, projection: Option<Mat4>) {
+        self.projection = projection;
+    }
+
+    /// Gets the current scale factor.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Sets the format of the target that following calls to [`Renderer::prepare`] prepare sprites for.
+    ///
+    /// This allows one renderer to draw to targets of different formats, e.g. a color target and a coverage target. Pipelines for each format are created on demand and cached. Sprites must be rendered to a target of the format they were prepared for.
+    pub fn set_texture_format(&mut self, texture_format: wgpu::TextureFormat) {
+        self.texture_format = texture_format;
+    }
+
+    /// Sets whether following calls to [`Renderer::prepare`] write coverage instead of colors, e.g. to build a reusable mask from the shapes of sprites.
+    ///
+    /// Coverage is the alpha of each sprite after tinting, written to every channel so that it lands in the red channel of single-channel targets such as [`wgpu::TextureFormat::R8Unorm`]. Overlapping coverage accumulates as with premultiplied alpha blending, regardless of each group's blend mode, unless blending is disabled with [`BlendMode::Opaque`] or [`Options::disable_blending`]. This cannot be used with [`Options::picking`].
+    pub fn set_coverage_output(&mut self, coverage_output: bool) {
+        self.coverage_output = coverage_output;
+    }
+
+    /// Gets the current target format.
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// Gets the number of groups prepared by the last [`Renderer::prepare`].
+    pub fn prepared_group_count(&self) -> usize {
+        self.prepared_groups.len()
+    }
+
+    /// Gets the IDs of the textures referenced by the last [`Renderer::prepare`], including alpha textures and the background texture.
+    ///
+    /// Each texture is only returned once. This can be used to decide which textures are safe to evict from a cache.
+    pub fn used_textures(&self) -> impl Iterator<Item = TextureId> + '_ {
+        self.prepared_groups
+            .iter()
+            .filter_map(|group| group.bind_group_key)
+            .flat_map(|key| std::iter::once(key.texture).chain(key.alpha_texture))
+            .chain(self.prepared_background_texture)
+            .unique()
+    }
+
+    /// Changes the tint of an item prepared by the last [`Renderer::prepare`], without preparing everything again, e.g. for hover highlights.
+    ///
+    /// Items are identified by the index of their group and their index within it, as given to [`Renderer::prepare`]. Only the tints of the item's vertices are written, replacing any [`Item::tints`] and [`Item::tint_f32`] and changes made to them in [`Renderer::prepare_with`]. Items that were skipped are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item doesn't exist, or if the renderer was created with [`Options::disable_tint`] or [`Options::picking`], as their vertices don't hold tints.
+    pub fn update_tint(&self, queue: &wgpu::Queue, group: usize, item: usize, tint: Color) {
+        assert!(
+            !self.pipelines.disable_tint() && !self.pipelines.picking(),
+            "spright: tints cannot be updated with Options::disable_tint or Options::picking"
+        );
+        let group_start = self.prepared_group_items[group];
+        let group_end = self
+            .prepared_group_items
+            .get(group + 1)
+            .copied()
+            .unwrap_or(self.prepared_items.len());
+        assert!(
+            group_start + item < group_end,
+            "spright: item {item} of group {group} was not prepared"
+        );
+
+        let tint = geometry::color_to_vec4(tint, self.srgb_tints);
+        for vertex in self.prepared_items[group_start + item].clone() {
+            queue.write_buffer(
+                &self.vertex_buffer.inner,
+                vertex as u64 * std::mem::size_of::<Vertex>() as u64
+                    + std::mem::offset_of!(Vertex, tint) as u64,
+                bytemuck::bytes_of(&tint.to_array()),
+            );
+        }
+    }
+
+    /// Checks if the last [`Renderer::prepare`] left nothing to draw.
+    ///
+    /// This can be used to skip beginning a render pass for idle frames.
+    pub fn is_empty(&self) -> bool {
+        self.prepared_groups
+            .iter()
+            .all(|group| group.index_buffer_start == group.index_buffer_end)
+    }
+
+    /// Gets the GPU memory used by the renderer's buffers.
+    ///
+    /// Buffers grow to fit the largest frame prepared so far and are never shrunk.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            vertex_buffer: self.vertex_buffer.size(),
+            index_buffer: self.index_buffer.size(),
+            uniform_buffers: self.target_uniforms_buffer.size()
+                + self.texture_uniforms_buffer.size(),
+        }
+    }
+
+    /// Gets the number of sprites that fit in the renderer's current buffers, i.e. how many can be prepared at once before the buffers have to grow.
+    ///
+    /// This assumes sprites with a single quad each, so subdivided items take up more room, and a background takes up the room of one sprite.
+    pub fn sprite_capacity(&self) -> usize {
+        let vertex_size = if self.pipelines.disable_tint() {
+            std::mem::size_of::<UntintedVertex>()
+        } else {
+            std::mem::size_of::<Vertex>()
+        } as u64;
+        let index_size = match self.index_format {
+            wgpu::IndexFormat::Uint16 => std::mem::size_of::<u16>(),
+            wgpu::IndexFormat::Uint32 => std::mem::size_of::<u32>(),
+        } as u64;
+        let capacity = (self.vertex_buffer.size() / (vertex_size * 4))
+            .min(self.index_buffer.size() / (index_size * 6));
+        (match self.index_format {
+            // Indices are absolute, so only this many vertices can be addressed.
+            wgpu::IndexFormat::Uint16 => capacity.min((u16::MAX as u64 + 1) / 4),
+            wgpu::IndexFormat::Uint32 => capacity,
+        }) as usize
+    }
+
+    /// Gets the blend mode a group is drawn with, taking [`Options::mask_blend_mode`] into account.
+    fn blend_mode_of(&self, group: &Group<'_>) -> BlendMode {
+        match self.mask_blend_mode {
+            Some(blend_mode) if group.texture.format() == wgpu::TextureFormat::R8Unorm => {
+                blend_mode
+            }
+            _ => group.blend_mode,
+        }
+    }
+
+    /// Creates the pipelines for the given blend modes and stencil modes ahead of time, for the current target format.
+    ///
+    /// Pipelines are otherwise created on first use in [`Renderer::prepare`], which may cause a hitch. Call this e.g. during a loading screen to avoid it.
+    pub fn prewarm(
+        &mut self,
+        device: &wgpu::Device,
+        blend_modes: &[BlendMode],
+        stencil_modes: &[StencilMode],
+    ) {
+        for &blend_mode in blend_modes {
+            for &stencil in stencil_modes {
+                self.pipelines.ensure(
+                    device,
+                    PipelineKey {
+                        blend_mode,
+                        stencil: stencil.without_reference(),
+                        depth: DepthMode::Disabled,
+                        texture_format: self.texture_format,
+                        coverage: self.coverage_output,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Prepares sprites for rendering.
+    ///
+    /// Groups are drawn in the order given, and items within each group are drawn in the order given, so later sprites are always drawn over earlier ones.
+    ///
+    /// Groups are used exactly as given and are never split or merged, so callers that already group their sprites by texture control the draw calls directly. [`batch::batch`] can be used to group a flat list of sprites instead.
     pub fn prepare(
         &mut self,
         device: &wgpu::Device,
@@ -289,7 +1434,57 @@ impl Renderer {
         target_size: wgpu::Extent3d,
         groups: &[Group<'_>],
     ) {
-        queue.write_buffer(&self.target_uniforms_buffer, 0, &{
+        self.prepare_with(device, queue, target_size, groups, |_| {});
+    }
+
+    /// Prepares sprites like [`Renderer::prepare`], but lets the generated vertices be modified before they are uploaded.
+    ///
+    /// This allows CPU-side vertex effects, e.g. displacing positions for a wave effect. Each item generates a grid of `(subdivisions.x + 1) * (subdivisions.y + 1)` vertices column by column from the top-left, i.e. top-left, bottom-left, top-right, bottom-right for an undivided quad, in the order the items were given. Items with an [`Item::uv_scroll`] generate one such grid for each piece the quad is split into. Vertices must not be added or removed.
+    pub fn prepare_with(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target_size: wgpu::Extent3d,
+        groups: &[Group<'_>],
+        modify_vertices: impl FnMut(&mut [Vertex]),
+    ) {
+        self.prepare_inner(
+            device,
+            &mut Uploader::Queue(queue),
+            target_size,
+            groups,
+            modify_vertices,
+        );
+    }
+
+    /// Prepares sprites like [`Renderer::prepare`], but records all uploads into a command encoder instead of writing them through the queue.
+    ///
+    /// Uploads are copied from staging buffers, so they are ordered relative to other commands recorded into the encoder. The encoder must be submitted before any commands rendering the prepared sprites.
+    pub fn prepare_with_encoder(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target_size: wgpu::Extent3d,
+        groups: &[Group<'_>],
+    ) {
+        self.prepare_inner(
+            device,
+            &mut Uploader::Encoder(encoder),
+            target_size,
+            groups,
+            |_| {},
+        );
+    }
+
+    fn prepare_inner(
+        &mut self,
+        device: &wgpu::Device,
+        uploader: &mut Uploader<'_>,
+        target_size: wgpu::Extent3d,
+        groups: &[Group<'_>],
+        mut modify_vertices: impl FnMut(&mut [Vertex]),
+    ) {
+        let target_uniforms = {
             let mut buffer = UniformBuffer::new(vec![]);
             buffer
                 .write(&TargetUniforms {
@@ -298,12 +1493,23 @@ impl Renderer {
                         y: target_size.height as f32,
                         z: 0.0,
                     },
+                    tint: geometry::color_to_vec4(self.global_tint, self.srgb_tints),
+                    dither: self.dither as u32,
+                    has_projection: self.projection.is_some() as u32,
+                    projection: self.projection.unwrap_or(Mat4::IDENTITY),
+                    vignette_strength: self.vignette.map_or(0.0, |v| v.strength),
+                    vignette_radius: self.vignette.map_or(0.0, |v| v.radius),
                 })
                 .unwrap();
             buffer.into_inner()
-        });
+        };
+        if target_uniforms != self.last_target_uniforms {
+            uploader.write_buffer(device, &self.target_uniforms_buffer, &target_uniforms);
+            self.last_target_uniforms = target_uniforms;
+        }
 
         self.prepared_groups.clear();
+        self.prepared_texture_format = self.texture_format;
 
         let min_uniform_buffer_offset_alignment =
             device.limits().min_uniform_buffer_offset_alignment;
@@ -313,150 +1519,448 @@ impl Renderer {
             min_uniform_buffer_offset_alignment as u64,
         );
 
-        for group in groups {
+        // Uniforms are padded to the alignment, so each group's offset comes from where it was written.
+        let texture_uniforms_offsets = groups
+            .iter()
+            .map(|group| {
+                texture_uniforms_buffer
+                    .write(&TextureUniforms {
+                        size: Vec3 {
+                            x: group.texture.width() as f32,
+                            y: group.texture.height() as f32,
+                            z: 0.0,
+                        },
+                        is_mask: (group.texture.format() == wgpu::TextureFormat::R8Unorm) as u32,
+                        premultiplied: matches!(
+                            self.blend_mode_of(group),
+                            BlendMode::Premultiplied | BlendMode::PremultipliedAdditive
+                        ) as u32,
+                        has_alpha_texture: group.alpha_texture.is_some() as u32,
+                        alpha_cutoff: group.alpha_cutoff,
+                        target_size: group.target_size.unwrap_or(Vec2::ZERO),
+                        uv_offset: group.uv_offset,
+                        mip_level: group.mip_level.unwrap_or(-1.0),
+                        mask_smoothing: group.mask_smoothing,
+                        checkerboard_cell_size: group
+                            .checkerboard
+                            .map_or(0.0, |checkerboard| checkerboard.cell_size),
+                        checkerboard_colors: group.checkerboard.map_or(
+                            [Vec4::ONE; 2],
+                            |checkerboard| {
+                                checkerboard
+                                    .colors
+                                    .map(|color| geometry::color_to_vec4(color, self.srgb_tints))
+                            },
+                        ),
+                    })
+                    .unwrap() as u32
+            })
+            .collect::<Vec<_>>();
+        let background_uniforms_offset = self.background.as_ref().map(|background| {
             texture_uniforms_buffer
                 .write(&TextureUniforms {
-                    size: Vec3 {
-                        x: group.texture.width() as f32,
-                        y: group.texture.height() as f32,
-                        z: 0.0,
-                    },
-                    is_mask: (group.texture.format() == wgpu::TextureFormat::R8Unorm) as u32,
+                    size: background.size.as_vec2().extend(0.0),
+                    is_mask: 0,
+                    premultiplied: 0,
+                    has_alpha_texture: 0,
+                    alpha_cutoff: 0.0,
+                    target_size: Vec2::ZERO,
+                    uv_offset: Vec2::ZERO,
+                    mip_level: -1.0,
+                    mask_smoothing: 0.0,
+                    checkerboard_cell_size: 0.0,
+                    checkerboard_colors: [Vec4::ONE; 2],
                 })
-                .unwrap();
-        }
+                .unwrap() as u32
+        });
 
-        self.texture_uniforms_buffer
-            .write(device, queue, &texture_uniforms_buffer.into_inner());
-
-        let mut vertices = vec![];
-        let mut indices = vec![];
-
-        for (i, group) in groups.into_iter().enumerate() {
-            let index_buffer_start = indices.len() as u32;
-
-            for item in group.items.iter() {
-                let offset = vertices.len() as u32;
-
-                let tint = [
-                    item.tint.r as f32 / 255.0,
-                    item.tint.g as f32 / 255.0,
-                    item.tint.b as f32 / 255.0,
-                    item.tint.a as f32 / 255.0,
-                ];
-
-                let left = item.src_offset.x;
-                let top = item.src_offset.y;
-                let right = item.src_offset.x + item.src_size.x as i32;
-                let bottom = item.src_offset.y + item.src_size.y as i32;
-
-                vertices.extend([
-                    Vertex {
-                        position: item
-                            .transform
-                            .transform_point2(Vec2::new(0.0, 0.0))
-                            .extend(0.0)
-                            .to_array(),
-                        tex_coords: [left as f32, top as f32],
-                        layer: item.src_layer,
-                        tint,
-                    },
-                    Vertex {
-                        position: item
-                            .transform
-                            .transform_point2(Vec2::new(0.0, item.src_size.y as f32))
-                            .extend(0.0)
-                            .to_array(),
-                        tex_coords: [left as f32, bottom as f32],
-                        layer: item.src_layer,
-                        tint,
-                    },
-                    Vertex {
-                        position: item
-                            .transform
-                            .transform_point2(Vec2::new(item.src_size.x as f32, 0.0))
-                            .extend(0.0)
-                            .to_array(),
-                        tex_coords: [right as f32, top as f32],
-                        layer: item.src_layer,
-                        tint,
-                    },
-                    Vertex {
-                        position: item
-                            .transform
-                            .transform_point2(Vec2::new(
-                                item.src_size.x as f32,
-                                item.src_size.y as f32,
-                            ))
-                            .extend(0.0)
-                            .to_array(),
-                        tex_coords: [right as f32, bottom as f32],
-                        layer: item.src_layer,
-                        tint,
-                    },
-                ]);
-
-                indices.extend(
-                    [
-                        0, 1, 2, //
-                        1, 2, 3,
-                    ]
-                    .map(|v| v + offset),
-                );
+        let texture_uniforms = texture_uniforms_buffer.into_inner();
+        if texture_uniforms != self.last_texture_uniforms {
+            if self
+                .texture_uniforms_buffer
+                .write(device, uploader, &texture_uniforms)
+            {
+                // Bind groups refer to the old buffer.
+                self.bind_groups.clear();
             }
+            self.last_texture_uniforms = texture_uniforms;
+        }
+
+        self.bind_groups.begin();
+
+        let geometry_options = geometry::GeometryOptions {
+            view: Affine2::from_scale(Vec2::splat(self.scale_factor)),
+            front_face: self.pipelines.front_face(),
+            picking: self.pipelines.picking(),
+            srgb_tints: self.srgb_tints,
+            pixel_snap: self.pixel_snap,
+        };
+        let mut geometry = geometry::build_geometry(groups, &geometry_options);
+
+        self.prepared_items = std::mem::take(&mut geometry.items);
+        self.prepared_group_items = groups
+            .iter()
+            .scan(0, |start, group| {
+                let group_start = *start;
+                *start += group.items.len();
+                Some(group_start)
+            })
+            .collect();
+
+        for ((group, indices), &texture_uniforms_offset) in groups
+            .iter()
+            .zip(geometry.groups.iter())
+            .zip(texture_uniforms_offsets.iter())
+        {
+            let pipeline_key = PipelineKey {
+                blend_mode: self.blend_mode_of(group),
+                stencil: group.stencil.without_reference(),
+                depth: group.depth,
+                texture_format: self.texture_format,
+                coverage: self.coverage_output,
+            };
+            self.pipelines.ensure(device, pipeline_key);
+
+            let bind_group_key = BindGroupKey {
+                texture: TextureId::of(group.texture),
+                alpha_texture: group.alpha_texture.map(TextureId::of),
+                sampler: group.sampler.map(hash_of),
+            };
+            self.bind_groups
+                .ensure(bind_group_key, group.texture, group.alpha_texture, || {
+                    let view = group.texture.create_view(&wgpu::TextureViewDescriptor {
+                        dimension: Some(wgpu::TextureViewDimension::D2Array),
+                        ..Default::default()
+                    });
+                    let alpha_view = group.alpha_texture.map(|alpha_texture| {
+                        alpha_texture.create_view(&wgpu::TextureViewDescriptor {
+                            dimension: Some(wgpu::TextureViewDimension::D2Array),
+                            ..Default::default()
+                        })
+                    });
+                    create_texture_bind_group(
+                        device,
+                        &self.texture_bind_group_layout,
+                        &self.texture_uniforms_buffer,
+                        &view,
+                        group.sampler.unwrap_or(&self.sampler),
+                        alpha_view.as_ref(),
+                    )
+                });
 
             self.prepared_groups.push(PreparedGroup {
-                texture_bind_group: device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("spright: texture_bind_group"),
-                    layout: &self.texture_bind_group_layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(
-                                &group.texture.create_view(&wgpu::TextureViewDescriptor {
-                                    dimension: Some(wgpu::TextureViewDimension::D2Array),
-                                    ..Default::default()
-                                }),
-                            ),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&self.sampler),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 2,
-                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                                buffer: &self.texture_uniforms_buffer,
-                                offset: (i * min_uniform_buffer_offset_alignment as usize) as u64,
-                                size: Some(TextureUniforms::SHADER_SIZE),
-                            }),
-                        },
-                    ],
-                }),
-                index_buffer_start,
-                index_buffer_end: indices.len() as u32,
+                pipeline_key,
+                stencil_reference: group.stencil.reference(),
+                bind_group_key: Some(bind_group_key),
+                texture_uniforms_offset,
+                index_buffer_start: indices.start,
+                index_buffer_end: indices.end,
             });
         }
 
-        self.vertex_buffer
-            .write(device, queue, bytemuck::cast_slice(&vertices[..]));
-        self.index_buffer
-            .write(device, queue, bytemuck::cast_slice(&indices[..]));
+        self.bind_groups.end();
+
+        modify_vertices(&mut geometry.vertices);
+
+        self.background_bind_group = None;
+        self.prepared_background_texture = self.background.as_ref().and_then(|b| b.texture);
+        if let (Some(background), Some(texture_uniforms_offset)) =
+            (&self.background, background_uniforms_offset)
+        {
+            let index_buffer_start = geometry.indices.len() as u32;
+            geometry::push_item(
+                &mut geometry,
+                &Item {
+                    src_size: background.size,
+                    tint: background.tint,
+                    transform: fit::fit(
+                        background.size,
+                        Rect {
+                            offset: IVec2::ZERO,
+                            size: uvec2(target_size.width, target_size.height),
+                        },
+                        background.fit,
+                    ),
+                    ..Default::default()
+                },
+                false,
+                &geometry::GeometryOptions {
+                    view: Affine2::IDENTITY,
+                    pixel_snap: None,
+                    ..geometry_options
+                },
+            );
+
+            let pipeline_key = PipelineKey {
+                blend_mode: BlendMode::default(),
+                stencil: StencilMode::Disabled,
+                depth: DepthMode::Disabled,
+                texture_format: self.texture_format,
+                coverage: self.coverage_output,
+            };
+            self.pipelines.ensure(device, pipeline_key);
+
+            self.background_bind_group = Some(create_texture_bind_group(
+                device,
+                &self.texture_bind_group_layout,
+                &self.texture_uniforms_buffer,
+                &background.view,
+                &self.sampler,
+                None,
+            ));
+            self.prepared_groups.insert(
+                0,
+                PreparedGroup {
+                    pipeline_key,
+                    stencil_reference: 0,
+                    bind_group_key: None,
+                    texture_uniforms_offset,
+                    index_buffer_start,
+                    index_buffer_end: geometry.indices.len() as u32,
+                },
+            );
+        }
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.prepare(device, self.prepared_groups.len());
+        }
+
+        assert!(
+            self.index_format == wgpu::IndexFormat::Uint32
+                || geometry.vertices.len() <= u16::MAX as usize + 1,
+            "spright: prepared sprites have more vertices than fit in Options::index_format"
+        );
+
+        if self.pipelines.disable_tint() {
+            let vertices = geometry
+                .vertices
+                .into_iter()
+                .map(UntintedVertex::from)
+                .collect::<Vec<_>>();
+            self.vertex_buffer
+                .write(device, uploader, bytemuck::cast_slice(&vertices[..]));
+        } else {
+            self.vertex_buffer.write(
+                device,
+                uploader,
+                bytemuck::cast_slice(&geometry.vertices[..]),
+            );
+        }
+        match self.index_format {
+            wgpu::IndexFormat::Uint16 => {
+                let mut indices = geometry
+                    .indices
+                    .iter()
+                    .map(|&i| i as u16)
+                    .collect::<Vec<_>>();
+                // Pad to a multiple of 4 bytes, as required for buffer writes.
+                if indices.len() % 2 != 0 {
+                    indices.push(0);
+                }
+                self.index_buffer
+                    .write(device, uploader, bytemuck::cast_slice(&indices[..]));
+            }
+            wgpu::IndexFormat::Uint32 => {
+                self.index_buffer.write(
+                    device,
+                    uploader,
+                    bytemuck::cast_slice(&geometry.indices[..]),
+                );
+            }
+        }
+    }
+
+    /// Prepares and renders a single sprite onto a target in one call.
+    ///
+    /// This replaces any previously prepared sprites, and is meant for occasional use, e.g. in tools and tests. Prefer batching sprites with [`Renderer::prepare`] when drawing many of them.
+    pub fn draw_one(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: wgpu::Extent3d,
+        sprite: &batch::Sprite<'_>,
+    ) {
+        self.prepare(
+            device,
+            queue,
+            target_size,
+            &batch::batch(std::slice::from_ref(sprite)),
+        );
+
+        self.render_with_target(
+            encoder,
+            target,
+            wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        );
+    }
+
+    fn prepared_bind_group(&self, prepared_group: &PreparedGroup) -> &wgpu::BindGroup {
+        match &prepared_group.bind_group_key {
+            Some(bind_group_key) => self.bind_groups.get(bind_group_key),
+            None => self.background_bind_group.as_ref().unwrap(),
+        }
+    }
+
+    /// Splits the indices of a group into draw calls of at most [`Options::max_indices_per_draw`] indices.
+    fn draw_ranges(
+        &self,
+        prepared_group: &PreparedGroup,
+    ) -> impl Iterator<Item = std::ops::Range<u32>> {
+        let end = prepared_group.index_buffer_end;
+        let step = self
+            .max_indices_per_draw
+            .map_or(u32::MAX, |max| (max / 3 * 3).max(3));
+        (prepared_group.index_buffer_start..end)
+            .step_by(step as usize)
+            .map(move |start| start..start.saturating_add(step).min(end))
+    }
+
+    /// Renders prepared sprites in a new render pass onto a target, with the given load and store operations.
+    ///
+    /// This covers the common case of a pass that only draws sprites to a single color target, e.g. clearing it first with [`wgpu::LoadOp::Clear`]. Use [`Renderer::render`] instead to draw into a pass shared with other draws, or one with a depth-stencil attachment or multisampling.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the renderer was created with [`Options::depth_stencil_format`], as the pass has no depth-stencil attachment.
+    pub fn render_with_target(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        ops: wgpu::Operations<wgpu::Color>,
+    ) {
+        assert!(
+            self.pipelines.depth_stencil_format().is_none(),
+            "spright: render_with_target cannot be used with Options::depth_stencil_format"
+        );
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("spright: render_with_target"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops,
+            })],
+            ..Default::default()
+        });
+        self.render(&mut rpass);
     }
 
     /// Renders prepared sprites.
-    pub fn render<'rpass>(&'rpass self, rpass: &mut wgpu::RenderPass<'rpass>) {
-        rpass.set_pipeline(&self.render_pipeline);
+    pub fn render(&self, rpass: &mut wgpu::RenderPass<'_>) {
         rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        rpass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
         rpass.set_bind_group(1, &self.target_uniforms_bind_group, &[]);
+        if let Some(empty_user_bind_group) = &self.empty_user_bind_group {
+            rpass.set_bind_group(2, empty_user_bind_group, &[]);
+        }
+        for (i, prepared_group) in self.prepared_groups.iter().enumerate() {
+            if let Some(profiler) = &self.profiler {
+                profiler.write(rpass, i);
+            }
+            rpass.set_pipeline(self.pipelines.get(&prepared_group.pipeline_key));
+            if prepared_group.pipeline_key.stencil != StencilMode::Disabled {
+                rpass.set_stencil_reference(prepared_group.stencil_reference);
+            }
+            rpass.set_bind_group(
+                0,
+                self.prepared_bind_group(prepared_group),
+                &[prepared_group.texture_uniforms_offset],
+            );
+            for indices in self.draw_ranges(prepared_group) {
+                rpass.draw_indexed(indices, 0, 0..1);
+            }
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.write(rpass, self.prepared_groups.len());
+        }
+    }
+
+    /// Checks if the renderer is measuring group draw times, i.e. [`Options::profiling`] was set and the device supports it.
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// Records resolving the draw times measured by the last [`Renderer::render`], for reading back with [`Renderer::read_profile`].
+    ///
+    /// This must be recorded after the render pass, and does nothing if the renderer isn't profiling.
+    pub fn resolve_profile(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(profiler) = &self.profiler {
+            profiler.resolve(encoder);
+        }
+    }
+
+    /// Reads back the draw times resolved by [`Renderer::resolve_profile`], once the commands have been submitted.
+    ///
+    /// The durations are in the order groups were drawn, starting with the background if there is one. This blocks until the GPU has finished, so it's meant for profiling rather than every frame. Returns `None` if the renderer isn't profiling, and an error if the durations couldn't be read back. Render bundles are not profiled.
+    pub fn read_profile(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<Result<Vec<std::time::Duration>, wgpu::BufferAsyncError>> {
+        self.profiler
+            .as_ref()
+            .map(|profiler| profiler.read(device, queue))
+    }
+
+    /// Records prepared sprites into a render bundle.
+    ///
+    /// The bundle doesn't borrow the renderer, and can be replayed into any render pass with a compatible target using [`wgpu::RenderPass::execute_bundles`]. It still draws from the renderer's buffers, so it must be replayed before the next [`Renderer::prepare`].
+    ///
+    /// For static content, sprites can be prepared and recorded once and the bundle replayed every frame, skipping the CPU cost of encoding the draws. The bundle must be recorded again after each [`Renderer::prepare`], as buffers may have been reallocated. [`Renderer::update_tint`] writes to the existing vertex buffer, so a recorded bundle picks up changed tints.
+    ///
+    /// The bundle targets the format the sprites were prepared for, even if [`Renderer::set_texture_format`] was called since.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any group uses the stencil buffer, as render bundles cannot set the stencil reference.
+    pub fn render_bundle(&self, device: &wgpu::Device) -> wgpu::RenderBundle {
+        assert!(
+            self.prepared_groups
+                .iter()
+                .all(|group| group.pipeline_key.stencil == StencilMode::Disabled),
+            "spright: render bundles cannot be used with stencil modes"
+        );
+
+        let mut encoder =
+            device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: Some("spright: render_bundle"),
+                color_formats: &[Some(self.prepared_texture_format)],
+                depth_stencil: self.pipelines.depth_stencil_format().map(|format| {
+                    wgpu::RenderBundleDepthStencil {
+                        format,
+                        depth_read_only: self
+                            .prepared_groups
+                            .iter()
+                            .all(|group| group.pipeline_key.depth != DepthMode::Prepass),
+                        stencil_read_only: true,
+                    }
+                }),
+                sample_count: 1,
+                multiview: None,
+            });
+        encoder.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        encoder.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        encoder.set_bind_group(1, &self.target_uniforms_bind_group, &[]);
+        if let Some(empty_user_bind_group) = &self.empty_user_bind_group {
+            encoder.set_bind_group(2, empty_user_bind_group, &[]);
+        }
         for prepared_group in self.prepared_groups.iter() {
-            rpass.set_bind_group(0, &prepared_group.texture_bind_group, &[]);
-            rpass.draw_indexed(
-                prepared_group.index_buffer_start..prepared_group.index_buffer_end,
+            encoder.set_pipeline(self.pipelines.get(&prepared_group.pipeline_key));
+            encoder.set_bind_group(
                 0,
-                0..1,
+                self.prepared_bind_group(prepared_group),
+                &[prepared_group.texture_uniforms_offset],
             );
+            for indices in self.draw_ranges(prepared_group) {
+                encoder.draw_indexed(indices, 0, 0..1);
+            }
         }
+        encoder.finish(&wgpu::RenderBundleDescriptor {
+            label: Some("spright: render_bundle"),
+        })
     }
 }
@@ -0,0 +1,187 @@
+use glam::*;
+
+/// Insets of the borders of a nine-slice sprite, in texels of the source region.
+///
+/// The corners are drawn unscaled, the edges are stretched along one axis and the center is stretched along both, e.g. for UI panels and buttons. All insets being zero stretches the whole source region instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NineSlice {
+    /// Width of the left border.
+    pub left: u32,
+
+    /// Height of the top border.
+    pub top: u32,
+
+    /// Width of the right border.
+    pub right: u32,
+
+    /// Height of the bottom border.
+    pub bottom: u32,
+}
+
+impl NineSlice {
+    /// Creates insets that are the same on all sides.
+    pub fn uniform(inset: u32) -> Self {
+        Self {
+            left: inset,
+            top: inset,
+            right: inset,
+            bottom: inset,
+        }
+    }
+}
+
+/// Stretches the source region of an item across a destination rectangle, using nine slices if any insets are set.
+///
+/// Each slice is a copy of the template item with its source region and transform replaced. The template's transform and [`crate::Item::corners`] are ignored, and its source region must not be rotated.
+///
+/// If the destination is smaller than the borders, the borders are scaled down to fit and the center is left out. Slices that would be empty are left out too.
+pub fn stretch(template: &crate::Item, dest: crate::Rect, slice: NineSlice) -> Vec<crate::Item> {
+    let src = template.src_rect();
+    if slice == NineSlice::default() {
+        return vec![crate::Item {
+            transform: crate::fit::fit(src.size, dest, crate::fit::FitMode::Fill),
+            corners: None,
+            ..template.clone()
+        }];
+    }
+
+    let xs = spans(src.size.x, dest.size.x, slice.left, slice.right);
+    let ys = spans(src.size.y, dest.size.y, slice.top, slice.bottom);
+
+    let mut items = vec![];
+    for &(src_y, src_height, dest_y, dest_height) in ys.iter() {
+        for &(src_x, src_width, dest_x, dest_width) in xs.iter() {
+            if src_width == 0 || src_height == 0 || dest_width <= 0.0 || dest_height <= 0.0 {
+                continue;
+            }
+            let src_size = uvec2(src_width, src_height);
+            items.push(crate::Item {
+                src_offset: src.offset + uvec2(src_x, src_y).as_ivec2(),
+                src_size,
+                transform: Affine2::from_scale_angle_translation(
+                    vec2(dest_width, dest_height) / src_size.as_vec2(),
+                    0.0,
+                    dest.offset.as_vec2() + vec2(dest_x, dest_y),
+                ),
+                corners: None,
+                ..template.clone()
+            });
+        }
+    }
+    items
+}
+
+/// Splits a source and destination length into the start border, the middle and the end border.
+///
+/// Returns the source offset, source length, destination offset and destination length of each span.
+fn spans(src: u32, dest: u32, start: u32, end: u32) -> [(u32, u32, f32, f32); 3] {
+    let start = start.min(src);
+    let end = end.min(src - start);
+    let middle = src - start - end;
+
+    let dest = dest as f32;
+    let borders = (start + end) as f32;
+    let scale = if borders > dest { dest / borders } else { 1.0 };
+    let dest_start = start as f32 * scale;
+    let dest_end = end as f32 * scale;
+
+    [
+        (0, start, 0.0, dest_start),
+        (start, middle, dest_start, dest - dest_start - dest_end),
+        (start + middle, end, dest - dest_end, dest_end),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the source region and the destination offset and size of each slice.
+    fn slices(dest: crate::Rect, slice: NineSlice) -> Vec<(crate::Rect, Vec2, Vec2)> {
+        let template = crate::Item {
+            src_offset: ivec2(100, 200),
+            src_size: uvec2(12, 12),
+            ..Default::default()
+        };
+        stretch(&template, dest, slice)
+            .iter()
+            .map(|item| {
+                (
+                    item.src_rect(),
+                    item.transform.translation,
+                    item.transform.transform_vector2(item.src_size.as_vec2()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stretches_edges_and_center() {
+        let dest = crate::Rect::new(ivec2(10, 20), uvec2(20, 16));
+        let slices = slices(dest, NineSlice::uniform(4));
+        assert_eq!(slices.len(), 9);
+
+        // Corners keep their size, while the center takes up the rest.
+        assert_eq!(
+            slices[0],
+            (
+                crate::Rect::new(ivec2(100, 200), uvec2(4, 4)),
+                vec2(10.0, 20.0),
+                vec2(4.0, 4.0)
+            )
+        );
+        assert_eq!(
+            slices[4],
+            (
+                crate::Rect::new(ivec2(104, 204), uvec2(4, 4)),
+                vec2(14.0, 24.0),
+                vec2(12.0, 8.0)
+            )
+        );
+        assert_eq!(
+            slices[8],
+            (
+                crate::Rect::new(ivec2(108, 208), uvec2(4, 4)),
+                vec2(26.0, 32.0),
+                vec2(4.0, 4.0)
+            )
+        );
+    }
+
+    #[test]
+    fn scales_borders_down_to_fit() {
+        // The borders add up to 8 texels on each axis but only have 6 pixels, so they are scaled by 3/4 and the edges and center are left out.
+        let dest = crate::Rect::new(ivec2(10, 20), uvec2(6, 6));
+        assert_eq!(
+            slices(dest, NineSlice::uniform(4)),
+            [
+                (ivec2(100, 200), vec2(10.0, 20.0)),
+                (ivec2(108, 200), vec2(13.0, 20.0)),
+                (ivec2(100, 208), vec2(10.0, 23.0)),
+                (ivec2(108, 208), vec2(13.0, 23.0)),
+            ]
+            .map(|(src_offset, offset)| (
+                crate::Rect::new(src_offset, uvec2(4, 4)),
+                offset,
+                vec2(3.0, 3.0)
+            ))
+        );
+
+        // Borders wider than the source region are clamped to it.
+        let slices = slices(
+            dest,
+            NineSlice {
+                left: 20,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            slices,
+            [(
+                crate::Rect::new(ivec2(100, 200), uvec2(12, 12)),
+                vec2(10.0, 20.0),
+                vec2(6.0, 6.0)
+            )]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use glam::*;
+
+/// Repeats an item along a polyline, e.g. for dashed lines and decorative borders.
+///
+/// Each copy of the item is scaled so that its height is `width`, rotated to follow the segment it starts on, and centered on the line. Copies are placed end to end with `spacing` between them, continuing across corners. The template's transform is ignored.
+///
+/// The last copy may extend past the end of the path.
+pub fn textured_path(
+    template: &crate::Item,
+    points: &[Vec2],
+    width: f32,
+    spacing: f32,
+) -> Vec<crate::Item> {
+    let src_size = template.src_size.as_vec2();
+    if src_size.x <= 0.0 || src_size.y <= 0.0 {
+        return vec![];
+    }
+
+    let scale = width / src_size.y;
+    let step = src_size.x * scale + spacing;
+    if step <= 0.0 {
+        return vec![];
+    }
+
+    let mut items = vec![];
+
+    // Distance along the current segment where the next copy starts.
+    let mut next = 0.0;
+
+    for segment in points.windows(2) {
+        let [start, end] = [segment[0], segment[1]];
+        let delta = end - start;
+        let length = delta.length();
+        if length == 0.0 {
+            continue;
+        }
+        let direction = delta / length;
+
+        while next < length {
+            items.push(crate::Item {
+                transform: Affine2::from_translation(start + direction * next)
+                    * Affine2::from_angle(direction.to_angle())
+                    * Affine2::from_scale(Vec2::splat(scale))
+                    * Affine2::from_translation(vec2(0.0, -src_size.y / 2.0)),
+                ..template.clone()
+            });
+            next += step;
+        }
+
+        next -= length;
+    }
+
+    items
+}
@@ -0,0 +1,62 @@
+use glam::*;
+
+/// Reads back the pick ID at a position of a target rendered with [`crate::Options::picking`].
+///
+/// The target must have been created with [`wgpu::TextureUsages::COPY_SRC`]. This blocks until the GPU has finished rendering, so it should be used sparingly, e.g. only when the cursor is clicked.
+pub fn read_pick_id(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    target: &wgpu::Texture,
+    position: UVec2,
+) -> u32 {
+    assert_eq!(
+        target.format(),
+        wgpu::TextureFormat::R32Uint,
+        "spright: pick targets must be R32Uint"
+    );
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("spright: pick readback"),
+        size: std::mem::size_of::<u32>() as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("spright: pick readback"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: target,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: position.x,
+                y: position.y,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: None,
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+
+    let id = bytemuck::pod_read_unaligned(&slice.get_mapped_range()[..]);
+    buffer.unmap();
+    id
+}
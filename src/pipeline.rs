@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crate::{BlendMode, DepthMode, StencilMode, UntintedVertex, Vertex};
+
+/// Identifies a render pipeline variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct PipelineKey {
+    pub blend_mode: BlendMode,
+    /// Stencil mode, without its reference value.
+    pub stencil: StencilMode,
+    pub depth: DepthMode,
+    pub texture_format: wgpu::TextureFormat,
+    /// Writes coverage instead of colors.
+    pub coverage: bool,
+}
+
+/// Lazily created render pipeline variants.
+pub(crate) struct Pipelines {
+    shader: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    disable_tint: bool,
+    disable_blending: bool,
+    picking: bool,
+    constants: HashMap<String, f64>,
+    primitive: wgpu::PrimitiveState,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+}
+
+impl Pipelines {
+    pub fn new(
+        shader: wgpu::ShaderModule,
+        layout: wgpu::PipelineLayout,
+        options: &crate::Options<'_>,
+    ) -> Self {
+        assert!(
+            !(options.disable_tint && options.picking),
+            "spright: Options::disable_tint cannot be used with Options::picking"
+        );
+
+        Self {
+            shader,
+            layout,
+            depth_stencil_format: options.depth_stencil_format,
+            disable_tint: options.disable_tint,
+            disable_blending: options.disable_blending,
+            picking: options.picking,
+            constants: HashMap::from([("lod_bias".to_string(), options.sampler.lod_bias as f64)]),
+            primitive: wgpu::PrimitiveState {
+                front_face: options.front_face,
+                cull_mode: options.cull_mode,
+                ..Default::default()
+            },
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Whether pipelines use [`UntintedVertex`] instead of [`Vertex`].
+    pub fn disable_tint(&self) -> bool {
+        self.disable_tint
+    }
+
+    /// Format of the depth-stencil attachment pipelines are compatible with.
+    pub fn depth_stencil_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_stencil_format
+    }
+
+    /// Winding order of front-facing triangles.
+    pub fn front_face(&self) -> wgpu::FrontFace {
+        self.primitive.front_face
+    }
+
+    /// Whether pipelines render pick IDs instead of colors.
+    pub fn picking(&self) -> bool {
+        self.picking
+    }
+
+    /// Creates the pipeline for the given key if it doesn't already exist.
+    pub fn ensure(&mut self, device: &wgpu::Device, key: PipelineKey) {
+        if self.pipelines.contains_key(&key) {
+            return;
+        }
+        let pipeline = self.create(device, key);
+        self.pipelines.insert(key, pipeline);
+    }
+
+    /// Replaces the shader, recreating all existing pipelines with it.
+    ///
+    /// Returns the previous shader and pipelines, for [`Pipelines::restore`].
+    pub fn replace_shader(
+        &mut self,
+        device: &wgpu::Device,
+        shader: wgpu::ShaderModule,
+    ) -> (
+        wgpu::ShaderModule,
+        HashMap<PipelineKey, wgpu::RenderPipeline>,
+    ) {
+        let shader = std::mem::replace(&mut self.shader, shader);
+        let pipelines = self
+            .pipelines
+            .keys()
+            .map(|&key| (key, self.create(device, key)))
+            .collect();
+        (shader, std::mem::replace(&mut self.pipelines, pipelines))
+    }
+
+    /// Restores the shader and pipelines returned by [`Pipelines::replace_shader`].
+    pub fn restore(
+        &mut self,
+        (shader, pipelines): (
+            wgpu::ShaderModule,
+            HashMap<PipelineKey, wgpu::RenderPipeline>,
+        ),
+    ) {
+        self.shader = shader;
+        self.pipelines = pipelines;
+    }
+
+    /// Drops all pipelines, to be created again on demand.
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+
+    /// Gets a pipeline that was previously created with [`Pipelines::ensure`].
+    pub fn get(&self, key: &PipelineKey) -> &wgpu::RenderPipeline {
+        &self.pipelines[key]
+    }
+
+    fn create(&self, device: &wgpu::Device, key: PipelineKey) -> wgpu::RenderPipeline {
+        assert!(
+            key.stencil == StencilMode::Disabled
+                || self
+                    .depth_stencil_format
+                    .is_some_and(|format| format.has_stencil_aspect()),
+            "spright: stencil modes require Options::depth_stencil_format to have a stencil aspect"
+        );
+        assert!(
+            key.depth == DepthMode::Disabled
+                || self
+                    .depth_stencil_format
+                    .is_some_and(|format| format.has_depth_aspect()),
+            "spright: depth modes require Options::depth_stencil_format to have a depth aspect"
+        );
+        assert!(
+            !self.picking || key.texture_format == wgpu::TextureFormat::R32Uint,
+            "spright: Options::picking requires an R32Uint texture format"
+        );
+        assert!(
+            !(self.picking && key.coverage),
+            "spright: coverage output cannot be used with Options::picking"
+        );
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("spright: render_pipeline"),
+            cache: None,
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader,
+                entry_point: Some(if self.picking {
+                    "vs_pick"
+                } else if self.disable_tint {
+                    "vs_main_untinted"
+                } else {
+                    "vs_main"
+                }),
+                buffers: &[if self.picking {
+                    Vertex::PICKING_BUFFER_LAYOUT
+                } else if self.disable_tint {
+                    UntintedVertex::BUFFER_LAYOUT
+                } else {
+                    Vertex::BUFFER_LAYOUT
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &self.constants,
+                    ..Default::default()
+                },
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &self.shader,
+                entry_point: Some(if self.picking {
+                    "fs_pick"
+                } else if key.coverage {
+                    "fs_coverage"
+                } else {
+                    "fs_main"
+                }),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &self.constants,
+                    ..Default::default()
+                },
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: key.texture_format,
+                    blend: if self.picking
+                        || self.disable_blending
+                        || key.blend_mode == BlendMode::Opaque
+                    {
+                        None
+                    } else if key.coverage {
+                        Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING)
+                    } else {
+                        key.blend_mode.blend_state()
+                    },
+                    write_mask: if matches!(key.stencil, StencilMode::Write(_))
+                        || key.depth == DepthMode::Prepass
+                    {
+                        wgpu::ColorWrites::empty()
+                    } else {
+                        wgpu::ColorWrites::all()
+                    },
+                })],
+            }),
+            primitive: self.primitive,
+            depth_stencil: self
+                .depth_stencil_format
+                .map(|format| wgpu::DepthStencilState {
+                    format,
+                    depth_write_enabled: key.depth.depth_write_enabled(),
+                    depth_compare: key.depth.depth_compare(),
+                    stencil: wgpu::StencilState {
+                        front: key.stencil.face_state(),
+                        back: key.stencil.face_state(),
+                        read_mask: !0,
+                        write_mask: !0,
+                    },
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+}
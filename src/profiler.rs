@@ -0,0 +1,119 @@
+/// Timestamp queries written around the draws of each prepared group.
+pub(crate) struct Profiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Number of queries the query set and buffers have room for.
+    capacity: u32,
+    /// Number of queries written by [`Profiler::write`] for the prepared groups.
+    count: u32,
+}
+
+impl Profiler {
+    /// Features a device needs for profiling.
+    pub const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES);
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let capacity = 64;
+        let (query_set, resolve_buffer, readback_buffer) = Self::create(device, capacity);
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            capacity,
+            count: 0,
+        }
+    }
+
+    fn create(
+        device: &wgpu::Device,
+        capacity: u32,
+    ) -> (wgpu::QuerySet, wgpu::Buffer, wgpu::Buffer) {
+        let size = capacity as u64 * std::mem::size_of::<u64>() as u64;
+        (
+            device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("spright: profiler_query_set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: capacity,
+            }),
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("spright: profiler_resolve_buffer"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("spright: profiler_readback_buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+        )
+    }
+
+    /// Makes room for the queries of a number of groups: one before each group, and one after the last.
+    pub fn prepare(&mut self, device: &wgpu::Device, group_count: usize) {
+        self.count = group_count as u32 + 1;
+        if self.count > self.capacity {
+            self.capacity = self.count.next_power_of_two();
+            (self.query_set, self.resolve_buffer, self.readback_buffer) =
+                Self::create(device, self.capacity);
+        }
+    }
+
+    /// Writes the query with the given index.
+    pub fn write(&self, rpass: &mut wgpu::RenderPass<'_>, index: usize) {
+        rpass.write_timestamp(&self.query_set, index as u32);
+    }
+
+    /// Resolves the written queries and copies them for reading back.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..self.count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.count as u64 * std::mem::size_of::<u64>() as u64,
+        );
+    }
+
+    /// Reads back the resolved queries as the time between each pair of consecutive queries.
+    ///
+    /// This blocks until the GPU has finished.
+    pub fn read(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<Vec<std::time::Duration>, wgpu::BufferAsyncError> {
+        // Nothing was prepared, or nothing was drawn between two queries.
+        if self.count < 2 {
+            return Ok(vec![]);
+        }
+
+        let slice = self
+            .readback_buffer
+            .slice(..self.count as u64 * std::mem::size_of::<u64>() as u64);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("spright: profiler readback was not mapped after polling")?;
+
+        let period = queue.get_timestamp_period() as f64;
+        let durations = bytemuck::cast_slice::<_, u64>(&slice.get_mapped_range()[..])
+            .windows(2)
+            .map(|pair| {
+                std::time::Duration::from_nanos(
+                    (pair[1].saturating_sub(pair[0]) as f64 * period) as u64,
+                )
+            })
+            .collect();
+        self.readback_buffer.unmap();
+        Ok(durations)
+    }
+}
@@ -0,0 +1,217 @@
+use glam::*;
+
+/// An axis-aligned rectangle with an integer offset and size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// Offset of the top-left corner.
+    pub offset: IVec2,
+
+    /// Size.
+    pub size: UVec2,
+}
+
+impl Rect {
+    /// Creates a new rectangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the right or bottom edge does not fit in an `i32`. Use [`Rect::try_new`] to handle this instead.
+    pub fn new(offset: IVec2, size: UVec2) -> Self {
+        Self::try_new(offset, size).expect("spright: rect edges must fit in an i32")
+    }
+
+    /// Creates a new rectangle, or returns `None` if the right or bottom edge does not fit in an `i32`.
+    pub fn try_new(offset: IVec2, size: UVec2) -> Option<Self> {
+        let rect = Self { offset, size };
+        rect.checked_right()?;
+        rect.checked_bottom()?;
+        Some(rect)
+    }
+
+    /// Gets the left edge.
+    pub fn left(&self) -> i32 {
+        self.offset.x
+    }
+
+    /// Gets the top edge.
+    pub fn top(&self) -> i32 {
+        self.offset.y
+    }
+
+    /// Gets the right edge, saturating at `i32::MAX`.
+    pub fn right(&self) -> i32 {
+        self.checked_right().unwrap_or(i32::MAX)
+    }
+
+    /// Gets the bottom edge, saturating at `i32::MAX`.
+    pub fn bottom(&self) -> i32 {
+        self.checked_bottom().unwrap_or(i32::MAX)
+    }
+
+    /// Gets the right edge, or `None` if it does not fit in an `i32`.
+    pub fn checked_right(&self) -> Option<i32> {
+        add_unsigned(self.offset.x, self.size.x)
+    }
+
+    /// Gets the bottom edge, or `None` if it does not fit in an `i32`.
+    pub fn checked_bottom(&self) -> Option<i32> {
+        add_unsigned(self.offset.y, self.size.y)
+    }
+
+    /// Returns the rectangle moved by an offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the moved rectangle's edges do not fit in an `i32`.
+    pub fn translated(&self, delta: IVec2) -> Self {
+        Self::new(
+            IVec2::new(
+                self.offset
+                    .x
+                    .checked_add(delta.x)
+                    .expect("spright: rect edges must fit in an i32"),
+                self.offset
+                    .y
+                    .checked_add(delta.y)
+                    .expect("spright: rect edges must fit in an i32"),
+            ),
+            self.size,
+        )
+    }
+
+    /// Returns the rectangle grown by an amount on all sides, or shrunk if the amount is negative.
+    ///
+    /// Shrinking a side to less than nothing leaves an empty rectangle at the center of that axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grown rectangle's edges do not fit in an `i32`.
+    pub fn inflated(&self, amount: i32) -> Self {
+        let inflate = |offset: i32, size: u32| {
+            let new_size = size as i64 + 2 * amount as i64;
+            if new_size < 0 {
+                (offset as i64 + size as i64 / 2, 0)
+            } else {
+                (offset as i64 - amount as i64, new_size)
+            }
+        };
+        let (x, width) = inflate(self.offset.x, self.size.x);
+        let (y, height) = inflate(self.offset.y, self.size.y);
+        let fit = |v: i64| i32::try_from(v).expect("spright: rect edges must fit in an i32");
+        let fit_size = |v: i64| u32::try_from(v).expect("spright: rect edges must fit in an i32");
+        Self::new(
+            IVec2::new(fit(x), fit(y)),
+            UVec2::new(fit_size(width), fit_size(height)),
+        )
+    }
+
+    /// Returns the rectangle with its offset and size multiplied by a factor, e.g. to convert from tiles to texels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scaled rectangle's edges do not fit in an `i32`.
+    pub fn scaled(&self, factor: u32) -> Self {
+        let scale = |v: i32| {
+            i32::try_from(v as i64 * factor as i64).expect("spright: rect edges must fit in an i32")
+        };
+        let scale_size = |v: u32| {
+            v.checked_mul(factor)
+                .expect("spright: rect edges must fit in an i32")
+        };
+        Self::new(
+            IVec2::new(scale(self.offset.x), scale(self.offset.y)),
+            UVec2::new(scale_size(self.size.x), scale_size(self.size.y)),
+        )
+    }
+}
+
+fn add_unsigned(offset: i32, size: u32) -> Option<i32> {
+    i32::try_from(offset as i64 + size as i64).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges() {
+        let rect = Rect::new(ivec2(-4, 2), uvec2(10, 3));
+        assert_eq!(
+            (rect.left(), rect.top(), rect.right(), rect.bottom()),
+            (-4, 2, 6, 5)
+        );
+        assert_eq!(rect.checked_right(), Some(6));
+        assert_eq!(rect.checked_bottom(), Some(5));
+
+        // The largest edges that fit, reached from negative offsets.
+        let rect = Rect::new(ivec2(i32::MIN, -1), uvec2(u32::MAX, i32::MAX as u32 + 1));
+        assert_eq!((rect.right(), rect.bottom()), (i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn overflowing_edges() {
+        assert_eq!(Rect::try_new(ivec2(i32::MAX, 0), uvec2(1, 0)), None);
+        assert_eq!(Rect::try_new(ivec2(0, 1), uvec2(0, i32::MAX as u32)), None);
+        assert!(Rect::try_new(ivec2(i32::MAX, i32::MAX), UVec2::ZERO).is_some());
+
+        // Rects built from their fields can still overflow, and saturate.
+        let rect = Rect {
+            offset: ivec2(i32::MAX, 0),
+            size: uvec2(u32::MAX, 1),
+        };
+        assert_eq!(rect.checked_right(), None);
+        assert_eq!(rect.right(), i32::MAX);
+        assert_eq!(rect.checked_bottom(), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "spright: rect edges must fit in an i32")]
+    fn new_panics_on_overflow() {
+        Rect::new(ivec2(1, 0), uvec2(i32::MAX as u32, 0));
+    }
+
+    #[test]
+    fn translated() {
+        let rect = Rect::new(ivec2(1, 2), uvec2(3, 4));
+        assert_eq!(
+            rect.translated(ivec2(-5, 6)),
+            Rect::new(ivec2(-4, 8), uvec2(3, 4))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "spright: rect edges must fit in an i32")]
+    fn translated_panics_on_overflow() {
+        Rect::new(ivec2(0, 0), uvec2(2, 2)).translated(ivec2(i32::MAX - 1, 0));
+    }
+
+    #[test]
+    fn inflated() {
+        let rect = Rect::new(ivec2(10, 20), uvec2(5, 8));
+        assert_eq!(rect.inflated(2), Rect::new(ivec2(8, 18), uvec2(9, 12)));
+        assert_eq!(rect.inflated(-2), Rect::new(ivec2(12, 22), uvec2(1, 4)));
+
+        // Shrinking past nothing collapses only the axes that are too small, around their centers.
+        assert_eq!(rect.inflated(-3), Rect::new(ivec2(12, 23), uvec2(0, 2)));
+        assert_eq!(rect.inflated(-10), Rect::new(ivec2(12, 24), UVec2::ZERO));
+    }
+
+    #[test]
+    #[should_panic(expected = "spright: rect edges must fit in an i32")]
+    fn inflated_panics_on_overflow() {
+        Rect::new(ivec2(i32::MIN, 0), uvec2(1, 1)).inflated(1);
+    }
+
+    #[test]
+    fn scaled() {
+        let rect = Rect::new(ivec2(-1, 2), uvec2(3, 4));
+        assert_eq!(rect.scaled(16), Rect::new(ivec2(-16, 32), uvec2(48, 64)));
+        assert_eq!(rect.scaled(0), Rect::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "spright: rect edges must fit in an i32")]
+    fn scaled_panics_on_overflow() {
+        Rect::new(ivec2(0, 0), uvec2(1 << 16, 1)).scaled(1 << 16);
+    }
+}
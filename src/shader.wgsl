@@ -1,3 +1,5 @@
+override lod_bias: f32 = 0.0;
+
 @group(0) @binding(0)
 var t: texture_2d_array<f32>;
 @group(0) @binding(1)
@@ -6,18 +8,46 @@ var s: sampler;
 struct TextureUniforms {
     size: vec3<f32>,
     is_mask: u32,
+    premultiplied: u32,
+    has_alpha_texture: u32,
+    alpha_cutoff: f32,
+    // Overrides the target size if non-zero.
+    target_size: vec2<f32>,
+    uv_offset: vec2<f32>,
+    // Selected automatically if negative.
+    mip_level: f32,
+    // Width of the smoothstep applied to masks, disabled if zero.
+    mask_smoothing: f32,
+    // Disabled if zero.
+    checkerboard_cell_size: f32,
+    checkerboard_colors: array<vec4<f32>, 2>,
 }
 
 @group(0) @binding(2)
 var<uniform> texture_uniforms: TextureUniforms;
 
+// Same as `t` if there is no separate alpha texture.
+@group(0) @binding(3)
+var alpha_t: texture_2d_array<f32>;
+
 struct TargetUniforms {
     size: vec3<f32>,
+    // Maps target pixels to clip space instead of the size if set.
+    has_projection: u32,
+    projection: mat4x4<f32>,
+    // Multiplied into the output of every fragment.
+    tint: vec4<f32>,
+    dither: u32,
+    // Disabled if zero.
+    vignette_strength: f32,
+    vignette_radius: f32,
 }
 
 @group(1) @binding(0)
 var<uniform> target_uniforms: TargetUniforms;
 
+// @group(2) is reserved for user globals.
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
@@ -25,6 +55,12 @@ struct VertexInput {
     @location(3) tint: vec4<f32>,
 }
 
+struct UntintedVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) layer: u32,
+}
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
@@ -32,27 +68,167 @@ struct VertexOutput {
     @location(2) tint: vec4<f32>,
 };
 
-@vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
+// Expands a mask sample to white with the mask as alpha, premultiplied if needed.
+//
+// After tinting, straight alpha gives (tint.rgb, tint.a * mask) and premultiplied alpha gives (tint.rgb * mask, tint.a * mask) for a premultiplied tint, so antialiased edges blend the same in both modes.
+fn mask_sample(sample: f32) -> vec4<f32> {
+    var mask = sample;
+    if texture_uniforms.mask_smoothing > 0.0 {
+        let half_width = texture_uniforms.mask_smoothing / 2.0;
+        mask = smoothstep(0.5 - half_width, 0.5 + half_width, mask);
+    }
+    if texture_uniforms.premultiplied == 1 {
+        return vec4(mask);
+    }
+    return vec4(1.0, 1.0, 1.0, mask);
+}
+
+// Samples at the group's mip level if set, otherwise with automatic selection.
+fn sample_color(texture: texture_2d_array<f32>, uv: vec2<f32>, layer: u32) -> vec4<f32> {
+    if texture_uniforms.mip_level < 0.0 {
+        return textureSampleBias(texture, s, uv, layer, lod_bias);
+    }
+    return textureSampleLevel(texture, s, uv, layer, texture_uniforms.mip_level);
+}
+
+// Gets the color of the checkerboard cell at a position, or white if disabled.
+fn checkerboard(position: vec2<f32>) -> vec4<f32> {
+    if texture_uniforms.checkerboard_cell_size <= 0.0 {
+        return vec4(1.0);
+    }
+    let cell = vec2<i32>(floor(position / texture_uniforms.checkerboard_cell_size));
+    return texture_uniforms.checkerboard_colors[(cell.x + cell.y) & 1];
+}
+
+// Darkens colors towards the edges of the target, if enabled.
+fn vignette(color: vec4<f32>, position: vec2<f32>) -> vec4<f32> {
+    if target_uniforms.vignette_strength == 0.0 {
+        return color;
+    }
+    // 0 at the center and 1 at the corners.
+    let distance = length(position / target_uniforms.size.xy - 0.5) * sqrt(2.0);
+    let darkening = target_uniforms.vignette_strength * smoothstep(target_uniforms.vignette_radius, 1.0, distance);
+    return vec4(color.rgb * (1.0 - darkening), color.a);
+}
+
+// Adds a 4x4 ordered dither pattern of up to half an 8-bit step, if enabled.
+fn dither(color: vec4<f32>, position: vec2<f32>) -> vec4<f32> {
+    if target_uniforms.dither == 0 {
+        return color;
+    }
+    let bayer = array(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let p = vec2<u32>(position) % 4;
+    let threshold = (bayer[p.y * 4 + p.x] + 0.5) / 16.0 - 0.5;
+    return vec4(color.rgb + threshold / 255.0, color.a);
+}
+
+fn vertex(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
     out.tint = model.tint;
 
+    out.tex_coords = model.tex_coords;
+    out.layer = model.layer;
+
+    if target_uniforms.has_projection == 1 {
+        out.position = target_uniforms.projection * vec4<f32>(model.position, 1.0);
+        return out;
+    }
+
     // Normalize screen position to NDC position.
-    var pos = (model.position.xy / target_uniforms.size.xy - 0.5) * 2.0;
+    var target_size = target_uniforms.size.xy;
+    if texture_uniforms.target_size.x > 0.0 {
+        target_size = texture_uniforms.target_size;
+    }
+    var pos = (model.position.xy / target_size - 0.5) * 2.0;
     pos.y = -pos.y;
 
-    out.tex_coords = model.tex_coords;
-    out.layer = model.layer;
-    out.position = vec4<f32>(pos, 0.0, 1.0);
+    out.position = vec4<f32>(pos, model.position.z, 1.0);
     return out;
 }
 
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    return vertex(model);
+}
+
+@vertex
+fn vs_main_untinted(model: UntintedVertexInput) -> VertexOutput {
+    return vertex(VertexInput(model.position, model.tex_coords, model.layer, vec4(1.0)));
+}
+
+// Samples and tints the color of a fragment, discarding it if it's below the alpha cutoff.
+fn shade(in: VertexOutput) -> vec4<f32> {
+    let uv = (in.tex_coords + texture_uniforms.uv_offset) / texture_uniforms.size.xy;
+    var sample = sample_color(t, uv, in.layer);
+    if texture_uniforms.is_mask == 1 {
+        sample = mask_sample(sample.r);
+    }
+    if texture_uniforms.has_alpha_texture == 1 {
+        sample.a = sample_color(alpha_t, uv, in.layer).r;
+    }
+    let color = sample * in.tint * checkerboard(in.position.xy);
+    if color.a < texture_uniforms.alpha_cutoff {
+        discard;
+    }
+    return color;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    var sample = textureSample(t, s, in.tex_coords / texture_uniforms.size.xy, in.layer);
+    let color = shade(in);
+    return dither(vignette(color * target_uniforms.tint, in.position.xy), in.position.xy);
+}
+
+// Writes coverage to every channel, premultiplied, so that it lands in the red channel of single-channel targets.
+@fragment
+fn fs_coverage(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4(shade(in).a * target_uniforms.tint.a);
+}
+
+struct PickVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) layer: u32,
+    @location(3) pick_id: vec4<u32>,
+}
+
+struct PickVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) layer: u32,
+    @location(2) @interpolate(flat) pick_id: u32,
+};
+
+@vertex
+fn vs_pick(model: PickVertexInput) -> PickVertexOutput {
+    let v = vertex(VertexInput(model.position, model.tex_coords, model.layer, vec4(1.0)));
+
+    var out: PickVertexOutput;
+    out.position = v.position;
+    out.tex_coords = v.tex_coords;
+    out.layer = v.layer;
+    out.pick_id = model.pick_id.x;
+    return out;
+}
+
+@fragment
+fn fs_pick(in: PickVertexOutput) -> @location(0) u32 {
+    let uv = (in.tex_coords + texture_uniforms.uv_offset) / texture_uniforms.size.xy;
+    var sample = textureSample(t, s, uv, in.layer);
     if texture_uniforms.is_mask == 1 {
-        sample = vec4(1.0, 1.0, 1.0, sample.r);
+        sample = mask_sample(sample.r);
+    }
+    if texture_uniforms.has_alpha_texture == 1 {
+        sample.a = textureSample(alpha_t, s, uv, in.layer).r;
+    }
+    if sample.a <= 0.0 || sample.a < texture_uniforms.alpha_cutoff {
+        discard;
     }
-    return sample * in.tint;
+    return in.pick_id;
 }
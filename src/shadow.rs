@@ -0,0 +1,69 @@
+use glam::*;
+
+/// Number of copies drawn around the sprite to blur its shadow.
+const BLUR_TAPS: usize = 8;
+
+/// How [`drop_shadow`] draws the shadow of an item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// Offset of the shadow from the item.
+    pub offset: Vec2,
+
+    /// Tint of the shadow.
+    ///
+    /// The shadow is drawn with the item's texture, so black gives a silhouette of the texture's alpha. Other colors are multiplied with the texture's colors, unless the texture is a mask.
+    pub color: crate::Color,
+
+    /// Radius of the blur, or 0.0 for a sharp shadow.
+    pub blur: f32,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            offset: vec2(2.0, 2.0),
+            color: crate::Color::new(0, 0, 0, 128),
+            blur: 0.0,
+        }
+    }
+}
+
+/// Draws an item on top of a drop shadow, e.g. for UI elements and text.
+///
+/// The shadow is a copy of the item moved by [`Shadow::offset`] and tinted with [`Shadow::color`], followed by the item itself. A blurred shadow is approximated by additional copies in a circle around the offset, with their alpha lowered so that overlapping copies add up to the shadow's alpha, rather than by filtering the texture.
+pub fn drop_shadow(item: &crate::Item, shadow: &Shadow) -> Vec<crate::Item> {
+    let offsets = if shadow.blur > 0.0 {
+        std::iter::once(Vec2::ZERO)
+            .chain((0..BLUR_TAPS).map(|i| {
+                Vec2::from_angle(std::f32::consts::TAU * i as f32 / BLUR_TAPS as f32) * shadow.blur
+            }))
+            .collect::<Vec<_>>()
+    } else {
+        vec![Vec2::ZERO]
+    };
+
+    // Alpha of each copy such that all copies blended over each other have the shadow's alpha.
+    let alpha = 1.0 - (1.0 - shadow.color.a as f32 / 255.0).powf(1.0 / offsets.len() as f32);
+    let tint = crate::Color {
+        a: (alpha * 255.0).round().max(1.0) as u8,
+        ..shadow.color
+    };
+
+    offsets
+        .into_iter()
+        .map(|offset| {
+            let offset = shadow.offset + offset;
+            crate::Item {
+                transform: Affine2::from_translation(offset) * item.transform,
+                corners: item
+                    .corners
+                    .map(|corners| corners.map(|corner| corner + offset)),
+                tint,
+                tint_f32: None,
+                tints: None,
+                ..item.clone()
+            }
+        })
+        .chain(std::iter::once(item.clone()))
+        .collect()
+}
@@ -0,0 +1,119 @@
+//! Helpers for creating textures from RGBA8 pixels.
+//!
+//! Textures are created with two identical layers, as the GL backend can only bind textures with multiple layers as arrays. Items should draw from layer 0, and the textures can be inserted into an [`crate::atlas::Atlas`].
+
+use glam::*;
+
+/// Creates a texture from straight (non-premultiplied) RGBA8 pixels, e.g. decoded from a PNG.
+///
+/// The format must be [`wgpu::TextureFormat::Rgba8Unorm`] or [`wgpu::TextureFormat::Rgba8UnormSrgb`]. The pixels are uploaded as is, for use with [`crate::BlendMode::Alpha`].
+pub fn create_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: UVec2,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+) -> wgpu::Texture {
+    assert!(
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb
+        ),
+        "spright: RGBA8 textures must be Rgba8Unorm or Rgba8UnormSrgb"
+    );
+    assert_eq!(
+        data.len(),
+        size.x as usize * size.y as usize * 4,
+        "spright: RGBA8 data must have 4 bytes per pixel"
+    );
+
+    create_layered_texture(device, queue, "spright: rgba8_texture", size, format, data)
+}
+
+/// Creates a texture from straight (non-premultiplied) RGBA8 pixels, premultiplying them during upload for use with [`crate::BlendMode::Premultiplied`].
+///
+/// See [`create_texture_rgba8`] for the supported formats. For [`wgpu::TextureFormat::Rgba8UnormSrgb`], colors are premultiplied in linear space, so that they are correctly premultiplied once the sampler decodes them.
+pub fn create_texture_rgba8_premultiplied(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: UVec2,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+) -> wgpu::Texture {
+    let srgb = format.is_srgb();
+    let premultiplied = data
+        .chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = pixel[3] as f32 / 255.0;
+            let premultiply = |c: u8| {
+                let c = c as f32 / 255.0;
+                let c = if srgb {
+                    linear_to_srgb(srgb_to_linear(c) * alpha)
+                } else {
+                    c * alpha
+                };
+                (c * 255.0).round() as u8
+            };
+            [
+                premultiply(pixel[0]),
+                premultiply(pixel[1]),
+                premultiply(pixel[2]),
+                pixel[3],
+            ]
+        })
+        .collect::<Vec<_>>();
+    create_texture_rgba8(device, queue, size, format, &premultiplied)
+}
+
+/// Creates a texture with two identical layers from the pixels of one layer, so that it can be bound as an array on every backend.
+///
+/// The texture can be copied from, e.g. into an [`crate::atlas::Atlas`].
+pub(crate) fn create_layered_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    size: UVec2,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+) -> wgpu::Texture {
+    use wgpu::util::DeviceExt as _;
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &[data, data].concat(),
+    )
+}
+
+/// Decodes an sRGB-encoded channel to linear.
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear channel to sRGB.
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,90 @@
+use glam::*;
+
+/// Fills a destination rectangle with copies of an item at its source size, laid out in a grid from the top-left, e.g. for fences and borders made of repeated sprites.
+///
+/// Copies along the right and bottom edges are clipped with [`crate::Item::reveal`] so that they don't extend past the destination. The template's transform, corners and reveal region are ignored, and its source region must not be rotated.
+///
+/// Unlike repeating texture coordinates with a sampler, every copy is a separate quad, so the source region can be part of an atlas.
+pub fn tile(template: &crate::Item, dest: crate::Rect) -> Vec<crate::Item> {
+    let src = template.src_rect();
+    if src.size.x == 0 || src.size.y == 0 {
+        return vec![];
+    }
+
+    let count = uvec2(
+        dest.size.x.div_ceil(src.size.x),
+        dest.size.y.div_ceil(src.size.y),
+    );
+    let mut items = Vec::with_capacity((count.x * count.y) as usize);
+    for y in 0..count.y {
+        for x in 0..count.x {
+            let offset = uvec2(x, y) * src.size;
+            let visible = src.size.min(dest.size - offset);
+            items.push(crate::Item {
+                transform: Affine2::from_translation((dest.offset + offset.as_ivec2()).as_vec2()),
+                corners: None,
+                reveal: (visible != src.size).then_some(crate::Rect {
+                    offset: src.offset,
+                    size: visible,
+                }),
+                ..template.clone()
+            });
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clips_partial_tiles() {
+        let template = crate::Item {
+            src_offset: ivec2(8, 0),
+            src_size: uvec2(4, 3),
+            ..Default::default()
+        };
+        let items = tile(&template, crate::Rect::new(ivec2(1, 2), uvec2(10, 4)));
+
+        // Three columns and two rows of tiles, with the last column 2 texels wide and the last row 1 texel tall.
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.transform.translation, item.reveal.map(|r| r.size)))
+                .collect::<Vec<_>>(),
+            [
+                (vec2(1.0, 2.0), None),
+                (vec2(5.0, 2.0), None),
+                (vec2(9.0, 2.0), Some(uvec2(2, 3))),
+                (vec2(1.0, 5.0), Some(uvec2(4, 1))),
+                (vec2(5.0, 5.0), Some(uvec2(4, 1))),
+                (vec2(9.0, 5.0), Some(uvec2(2, 1))),
+            ]
+        );
+
+        // Reveal regions start at the source region, keeping the top-left of each tile.
+        assert!(items
+            .iter()
+            .filter_map(|item| item.reveal)
+            .all(|reveal| reveal.offset == ivec2(8, 0)));
+    }
+
+    #[test]
+    fn exact_and_empty_fills() {
+        let template = crate::Item {
+            src_size: uvec2(4, 4),
+            ..Default::default()
+        };
+        let items = tile(&template, crate::Rect::new(IVec2::ZERO, uvec2(8, 4)));
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.reveal.is_none()));
+
+        assert!(tile(&template, crate::Rect::new(IVec2::ZERO, uvec2(0, 4))).is_empty());
+        assert!(tile(
+            &crate::Item::default(),
+            crate::Rect::new(IVec2::ZERO, uvec2(8, 4))
+        )
+        .is_empty());
+    }
+}
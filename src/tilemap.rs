@@ -0,0 +1,262 @@
+use encase::{ShaderType, UniformBuffer};
+use glam::*;
+
+/// Tile index of an empty cell.
+pub const EMPTY: u32 = u32::MAX;
+
+// The `ShaderType` derive emits helper items that newer compilers flag as dead code, and those don't
+// inherit attributes from the struct, so the allowance covers this module.
+#[allow(dead_code)]
+mod uniforms {
+    use encase::ShaderType;
+    use glam::*;
+
+    #[derive(ShaderType)]
+    pub(crate) struct TilemapUniforms {
+        pub(crate) transform: Mat3,
+        pub(crate) target_size: Vec2,
+        pub(crate) tile_size: Vec2,
+        pub(crate) texture_size: Vec2,
+        pub(crate) grid_width: u32,
+        pub(crate) columns: u32,
+        pub(crate) layer: u32,
+    }
+}
+
+use uniforms::TilemapUniforms;
+
+/// Renders [`Tilemap`]s.
+///
+/// Unlike [`crate::Renderer`], tile positions and texture coordinates are computed on the GPU from a grid of tile indices, so each tile only takes 4 bytes and tiles don't need to be uploaded every frame.
+pub struct TilemapRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+impl TilemapRenderer {
+    /// Creates a new tilemap renderer.
+    pub fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("tilemap.wgsl"));
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("spright: tilemap_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("spright: tilemap_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("spright: tilemap_pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<u32>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![0 => Uint32],
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: crate::BlendMode::Alpha.blend_state(),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            sampler: device.create_sampler(&wgpu::SamplerDescriptor::default()),
+        }
+    }
+
+    /// Renders a tilemap.
+    pub fn render(&self, rpass: &mut wgpu::RenderPass<'_>, tilemap: &Tilemap) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &tilemap.bind_group, &[]);
+        rpass.set_vertex_buffer(0, tilemap.tiles_buffer.slice(..));
+        rpass.draw(0..4, 0..tilemap.grid_size.x * tilemap.grid_size.y);
+    }
+}
+
+/// A grid of tiles drawn from a single layer of a tileset texture.
+///
+/// Tiles are numbered left to right, then top to bottom, in tiles of `tile_size` starting from the top-left of the tileset.
+pub struct Tilemap {
+    grid_size: UVec2,
+    tile_size: UVec2,
+    columns: u32,
+    layer: u32,
+    texture_size: UVec2,
+    tiles_buffer: wgpu::Buffer,
+    uniforms_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Tilemap {
+    /// Creates a new tilemap with all cells set to [`EMPTY`].
+    pub fn new(
+        device: &wgpu::Device,
+        renderer: &TilemapRenderer,
+        tileset: &wgpu::Texture,
+        layer: u32,
+        grid_size: UVec2,
+        tile_size: UVec2,
+    ) -> Self {
+        assert!(
+            tile_size.x > 0 && tile_size.y > 0,
+            "spright: tile size must not be zero"
+        );
+
+        let tiles_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("spright: tilemap_tiles_buffer"),
+            size: (grid_size.x * grid_size.y).max(1) as u64 * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: true,
+        });
+        tiles_buffer.slice(..).get_mapped_range_mut().fill(0xff);
+        tiles_buffer.unmap();
+
+        let uniforms_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("spright: tilemap_uniforms_buffer"),
+            size: TilemapUniforms::min_size().get(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("spright: tilemap_bind_group"),
+            layout: &renderer.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&tileset.create_view(
+                        &wgpu::TextureViewDescriptor {
+                            dimension: Some(wgpu::TextureViewDimension::D2Array),
+                            ..Default::default()
+                        },
+                    )),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&renderer.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniforms_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            grid_size,
+            tile_size,
+            columns: (tileset.width() / tile_size.x).max(1),
+            layer,
+            texture_size: uvec2(tileset.width(), tileset.height()),
+            tiles_buffer,
+            uniforms_buffer,
+            bind_group,
+        }
+    }
+
+    /// Gets the size of the grid, in tiles.
+    pub fn grid_size(&self) -> UVec2 {
+        self.grid_size
+    }
+
+    /// Replaces all tiles, in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of tiles does not match the size of the grid.
+    pub fn set_tiles(&self, queue: &wgpu::Queue, tiles: &[u32]) {
+        assert_eq!(
+            tiles.len(),
+            (self.grid_size.x * self.grid_size.y) as usize,
+            "spright: tile count must match the grid size"
+        );
+        queue.write_buffer(&self.tiles_buffer, 0, bytemuck::cast_slice(tiles));
+    }
+
+    /// Sets a single tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside the grid.
+    pub fn set_tile(&self, queue: &wgpu::Queue, position: UVec2, tile: u32) {
+        assert!(
+            position.x < self.grid_size.x && position.y < self.grid_size.y,
+            "spright: tile position must be inside the grid"
+        );
+        let index = position.y * self.grid_size.x + position.x;
+        queue.write_buffer(
+            &self.tiles_buffer,
+            index as u64 * std::mem::size_of::<u32>() as u64,
+            bytemuck::bytes_of(&tile),
+        );
+    }
+
+    /// Prepares the tilemap for rendering with the given target size and transform.
+    ///
+    /// The transform maps tilemap pixels, with the top-left of the first tile at the origin, to target pixels.
+    pub fn prepare(&self, queue: &wgpu::Queue, target_size: wgpu::Extent3d, transform: Affine2) {
+        let mut buffer = UniformBuffer::new(vec![]);
+        buffer
+            .write(&TilemapUniforms {
+                transform: Mat3::from(transform),
+                target_size: vec2(target_size.width as f32, target_size.height as f32),
+                tile_size: self.tile_size.as_vec2(),
+                texture_size: self.texture_size.as_vec2(),
+                grid_width: self.grid_size.x.max(1),
+                columns: self.columns,
+                layer: self.layer,
+            })
+            .unwrap();
+        queue.write_buffer(&self.uniforms_buffer, 0, &buffer.into_inner());
+    }
+}
@@ -0,0 +1,54 @@
+@group(0) @binding(0)
+var t: texture_2d_array<f32>;
+@group(0) @binding(1)
+var s: sampler;
+
+struct TilemapUniforms {
+    transform: mat3x3<f32>,
+    target_size: vec2<f32>,
+    tile_size: vec2<f32>,
+    texture_size: vec2<f32>,
+    grid_width: u32,
+    columns: u32,
+    layer: u32,
+}
+
+@group(0) @binding(2)
+var<uniform> uniforms: TilemapUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+    @location(0) tile: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Empty tiles collapse into a degenerate quad.
+    if tile == 0xffffffffu {
+        return out;
+    }
+
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+    let cell = vec2<f32>(f32(instance_index % uniforms.grid_width), f32(instance_index / uniforms.grid_width));
+    let position = (uniforms.transform * vec3<f32>((cell + corner) * uniforms.tile_size, 1.0)).xy;
+
+    // Normalize screen position to NDC position.
+    var pos = (position / uniforms.target_size - 0.5) * 2.0;
+    pos.y = -pos.y;
+
+    let src = vec2<f32>(f32(tile % uniforms.columns), f32(tile / uniforms.columns));
+    out.tex_coords = (src + corner) * uniforms.tile_size / uniforms.texture_size;
+    out.position = vec4<f32>(pos, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t, s, in.tex_coords, uniforms.layer);
+}
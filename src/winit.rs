@@ -0,0 +1,68 @@
+//! Helpers for rendering to a [`wgpu::Surface`] of a [`winit`] window.
+//!
+//! This module requires the `winit` feature.
+
+use winit::window::Window;
+
+/// Configures a surface to the current size of its window.
+///
+/// This should be called when the surface is created and whenever the window is resized.
+pub fn configure_surface(
+    device: &wgpu::Device,
+    surface: &wgpu::Surface<'_>,
+    window: &Window,
+    format: wgpu::TextureFormat,
+) {
+    let size = window.inner_size();
+    surface.configure(
+        device,
+        &wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        },
+    );
+}
+
+/// Renders a frame to a surface and presents it.
+///
+/// The frame is cleared to `clear_color`, and `groups` is called with the size of the frame to produce the groups to draw. The renderer's target format is set to the format of the frame, so it follows the surface if it is reconfigured with a different format. If the surface is outdated or lost, the error is returned so that it can be reconfigured with [`configure_surface`].
+pub fn render_to_surface<'a>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    surface: &wgpu::Surface<'_>,
+    window: &Window,
+    renderer: &mut crate::Renderer,
+    clear_color: wgpu::Color,
+    groups: impl FnOnce(wgpu::Extent3d) -> Vec<crate::Group<'a>>,
+) -> Result<(), wgpu::SurfaceError> {
+    let frame = surface.get_current_texture()?;
+    let size = frame.texture.size();
+    renderer.set_texture_format(frame.texture.format());
+    renderer.prepare(device, queue, size, &groups(size));
+
+    let view = frame
+        .texture
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("spright: render_to_surface"),
+    });
+    renderer.render_with_target(
+        &mut encoder,
+        &view,
+        wgpu::Operations {
+            load: wgpu::LoadOp::Clear(clear_color),
+            store: wgpu::StoreOp::Store,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    window.pre_present_notify();
+    frame.present();
+    Ok(())
+}
@@ -0,0 +1,293 @@
+mod common;
+
+use common::*;
+use glam::*;
+use spright::batch::{self, BatchStrategy, Sprite};
+use spright::{Color, Group, Rect, Renderer, TextureId};
+
+/// Creates a sprite whose source offset identifies it.
+fn sprite(texture: &wgpu::Texture, id: i32, order: f32) -> Sprite<'_> {
+    Sprite {
+        order,
+        ..Sprite::new(
+            texture,
+            Rect::new(ivec2(id, 0), UVec2::ONE),
+            Affine2::IDENTITY,
+            Color::new(255, 255, 255, 255),
+        )
+    }
+}
+
+/// Summarizes groups as their textures and the IDs of their sprites.
+fn summarize(groups: &[Group<'_>]) -> Vec<(TextureId, Vec<i32>)> {
+    groups
+        .iter()
+        .map(|group| {
+            (
+                TextureId::of(group.texture),
+                group.items.iter().map(|item| item.src_offset.x).collect(),
+            )
+        })
+        .collect()
+}
+
+fn textures(context: &Context) -> (wgpu::Texture, wgpu::Texture) {
+    let texture = || {
+        texture(
+            context,
+            uvec2(4, 1),
+            wgpu::TextureFormat::Rgba8Unorm,
+            &[255; 16],
+        )
+    };
+    (texture(), texture())
+}
+
+#[test]
+fn batch_preserves_submission_order() {
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, b) = textures(context);
+    let (a_id, b_id) = (TextureId::of(&a), TextureId::of(&b));
+    let sprites = [
+        sprite(&a, 0, 0.0),
+        sprite(&a, 1, 0.0),
+        sprite(&b, 2, 0.0),
+        sprite(&b, 3, 0.0),
+        sprite(&a, 4, 0.0),
+    ];
+
+    // Groups are in the order of their first sprite, and a texture that comes back later gets a new group rather than joining its earlier one.
+    assert_eq!(
+        summarize(&batch::batch(&sprites)),
+        [(a_id, vec![0, 1]), (b_id, vec![2, 3]), (a_id, vec![4])]
+    );
+}
+
+#[test]
+fn batch_by_order_keeps_ties_in_submission_order() {
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, b) = textures(context);
+    let (a_id, b_id) = (TextureId::of(&a), TextureId::of(&b));
+    let sprites = [
+        sprite(&a, 0, 1.0),
+        sprite(&b, 1, 0.0),
+        sprite(&a, 2, 1.0),
+        sprite(&b, 3, 0.0),
+        sprite(&a, 4, 0.0),
+    ];
+
+    assert_eq!(
+        summarize(&batch::batch_by_order(&sprites)),
+        [(b_id, vec![1, 3]), (a_id, vec![4, 0, 2])]
+    );
+}
+
+#[test]
+fn later_sprites_are_drawn_on_top() {
+    let Some(context) = context() else {
+        return;
+    };
+    let red = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 255],
+    );
+    let green = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 255, 0, 255],
+    );
+    let white = Color::new(255, 255, 255, 255);
+    let at = |x: f32| Affine2::from_translation(vec2(x, 0.0));
+
+    // Pixel 0 is drawn red then green, and pixel 1 green then red, alternating between textures.
+    let sprites = [
+        Sprite::new(&red, Rect::new(IVec2::ZERO, UVec2::ONE), at(0.0), white),
+        Sprite::new(&green, Rect::new(IVec2::ZERO, UVec2::ONE), at(0.0), white),
+        Sprite::new(&green, Rect::new(IVec2::ZERO, UVec2::ONE), at(1.0), white),
+        Sprite::new(&red, Rect::new(IVec2::ZERO, UVec2::ONE), at(1.0), white),
+    ];
+
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_groups(
+        context,
+        &mut renderer,
+        uvec2(2, 1),
+        wgpu::Color::BLACK,
+        &batch::batch(&sprites),
+    );
+    assert_pixels(&pixels, &[[0, 255, 0, 255], [255, 0, 0, 255]]);
+}
+
+#[test]
+fn color_and_mask_sprites_are_drawn_in_separate_groups() {
+    let Some(context) = context() else {
+        return;
+    };
+    let color = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 255, 0, 255],
+    );
+    let mask = texture(context, uvec2(1, 1), wgpu::TextureFormat::R8Unorm, &[255]);
+    let yellow = Color::new(255, 255, 0, 255);
+    let src = Rect::new(IVec2::ZERO, UVec2::ONE);
+    let at = |x: f32| Affine2::from_translation(vec2(x, 0.0));
+    let sprites = [
+        Sprite::new(&color, src, at(0.0), yellow),
+        Sprite::new(&mask, src, at(1.0), yellow),
+        Sprite::new(&color, src, at(2.0), yellow),
+    ];
+
+    let groups = batch::batch(&sprites);
+    assert_eq!(
+        groups
+            .iter()
+            .map(|group| group.texture.format())
+            .collect::<Vec<_>>(),
+        [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::R8Unorm,
+            wgpu::TextureFormat::Rgba8Unorm
+        ]
+    );
+
+    // The color texture is multiplied by the tint, while the mask takes the tint's color. Drawing the mask as a color texture would give red instead.
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_groups(
+        context,
+        &mut renderer,
+        uvec2(3, 1),
+        wgpu::Color::BLACK,
+        &groups,
+    );
+    assert_pixels(
+        &pixels,
+        &[[0, 255, 0, 255], [255, 255, 0, 255], [0, 255, 0, 255]],
+    );
+}
+
+#[test]
+fn by_texture_draws_each_texture_once() {
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, b) = textures(context);
+    let sprites = [
+        sprite(&a, 0, 0.0),
+        sprite(&b, 1, 0.0),
+        sprite(&a, 2, 0.0),
+        sprite(&b, 3, 0.0),
+    ];
+
+    let mut groups = summarize(&batch::batch_with(&sprites, &batch::ByTexture));
+    groups.sort();
+    let mut expected = vec![
+        (TextureId::of(&a), vec![0, 2]),
+        (TextureId::of(&b), vec![1, 3]),
+    ];
+    expected.sort();
+    assert_eq!(groups, expected);
+}
+
+#[test]
+fn custom_strategy() {
+    struct Reversed;
+
+    impl BatchStrategy for Reversed {
+        fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+            sprites.iter().rev().collect()
+        }
+    }
+
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, b) = textures(context);
+    let sprites = [sprite(&a, 0, 0.0), sprite(&a, 1, 0.0), sprite(&b, 2, 0.0)];
+
+    assert_eq!(
+        summarize(&batch::batch_with(&sprites, &Reversed)),
+        [
+            (TextureId::of(&b), vec![2]),
+            (TextureId::of(&a), vec![1, 0])
+        ]
+    );
+}
+
+#[test]
+fn sprite_builder_default_tint() {
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, _) = textures(context);
+    let teal = Color::new(0, 128, 128, 255);
+    let red = Color::new(255, 0, 0, 255);
+    let src = Rect::new(IVec2::ZERO, UVec2::ONE);
+
+    let mut builder = batch::SpriteBuilder::new(&a);
+    builder.sprite(src, Affine2::IDENTITY);
+    assert_eq!(builder.sprites()[0].tint, Color::new(255, 255, 255, 255));
+
+    let mut builder = batch::SpriteBuilder::new(&a).tint(teal);
+    builder
+        .sprite(src, Affine2::IDENTITY)
+        .tinted_sprite(src, Affine2::IDENTITY, red)
+        .sprite(src, Affine2::IDENTITY);
+    assert_eq!(
+        builder
+            .sprites()
+            .iter()
+            .map(|sprite| sprite.tint)
+            .collect::<Vec<_>>(),
+        [teal, red, teal]
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn strategies_that_drop_or_duplicate_sprites_panic() {
+    struct Dropping;
+
+    impl BatchStrategy for Dropping {
+        fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+            sprites.iter().skip(1).collect()
+        }
+    }
+
+    struct Duplicating;
+
+    impl BatchStrategy for Duplicating {
+        fn arrange<'a>(&self, sprites: &'a [Sprite<'a>]) -> Vec<&'a Sprite<'a>> {
+            sprites.iter().chain(sprites).collect()
+        }
+    }
+
+    let Some(context) = context() else {
+        return;
+    };
+    let (a, _) = textures(context);
+    let sprites = [sprite(&a, 0, 0.0), sprite(&a, 1, 0.0)];
+
+    for result in [
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            batch::batch_with(&sprites, &Dropping);
+        })),
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            batch::batch_with(&sprites, &Duplicating);
+        })),
+    ] {
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(
+            message.contains("spright: batch strategy must not drop or duplicate sprites"),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+#![allow(dead_code)]
+
+use glam::*;
+use wgpu::util::DeviceExt as _;
+
+/// Format of targets rendered to by [`render`].
+pub const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+pub struct Context {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+/// Gets a headless device shared by all tests, or `None` if there is no adapter to run them on.
+pub fn context() -> Option<&'static Context> {
+    static CONTEXT: std::sync::OnceLock<Option<Context>> = std::sync::OnceLock::new();
+    CONTEXT
+        .get_or_init(|| {
+            let instance = wgpu::Instance::default();
+            match pollster::block_on(spright::headless::request_device(&instance)) {
+                Ok((_, device, queue)) => Some(Context { device, queue }),
+                Err(spright::headless::Error::NoAdapter) => {
+                    eprintln!("skipping GPU tests: no adapter");
+                    None
+                }
+                Err(e) => panic!("{e}"),
+            }
+        })
+        .as_ref()
+}
+
+/// Gets a device like [`context`], but with the adapter's own uniform buffer offset alignment, which can be smaller than the uniforms of a group.
+pub fn aligned_context() -> Option<&'static Context> {
+    static CONTEXT: std::sync::OnceLock<Option<Context>> = std::sync::OnceLock::new();
+    CONTEXT
+        .get_or_init(|| {
+            let context = context()?;
+            let instance = wgpu::Instance::default();
+            let (adapter, _, _) =
+                pollster::block_on(spright::headless::request_device(&instance)).unwrap();
+            let (device, queue) = pollster::block_on(adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    required_limits: wgpu::Limits {
+                        min_uniform_buffer_offset_alignment:
+                            adapter.limits().min_uniform_buffer_offset_alignment,
+                        ..context.device.limits()
+                    },
+                    ..Default::default()
+                },
+                None,
+            ))
+            .unwrap();
+            Some(Context { device, queue })
+        })
+        .as_ref()
+}
+
+/// Creates a texture with the given pixels in layer 0.
+///
+/// The texture has two layers so that it can be bound as an array on GL.
+pub fn texture(
+    context: &Context,
+    size: UVec2,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+) -> wgpu::Texture {
+    context.device.create_texture_with_data(
+        &context.queue,
+        &wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &[data, data].concat(),
+    )
+}
+
+/// Creates an item drawing a whole texture of the given size.
+pub fn item(size: UVec2, transform: Affine2, tint: spright::Color) -> spright::Item {
+    spright::Item {
+        src_size: size,
+        transform,
+        tint,
+        ..Default::default()
+    }
+}
+
+/// Prepares a renderer, renders it into a target cleared to a color, and reads the target back.
+pub fn render(
+    context: &Context,
+    renderer: &mut spright::Renderer,
+    size: UVec2,
+    clear: wgpu::Color,
+    prepare: impl FnOnce(&mut spright::Renderer, &Context, wgpu::Extent3d),
+) -> Vec<[u8; 4]> {
+    draw(context, size, |encoder, view, target_size| {
+        prepare(renderer, context, target_size);
+        renderer.render_with_target(
+            encoder,
+            view,
+            wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear),
+                store: wgpu::StoreOp::Store,
+            },
+        );
+    })
+}
+
+/// Creates a render pass on a target cleared to a color, draws into it, and reads the target back.
+pub fn render_pass(
+    context: &Context,
+    size: UVec2,
+    clear: wgpu::Color,
+    draw_pass: impl FnOnce(&mut wgpu::RenderPass<'_>, wgpu::Extent3d),
+) -> Vec<[u8; 4]> {
+    draw(context, size, |encoder, view, target_size| {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        draw_pass(&mut rpass, target_size);
+    })
+}
+
+/// Creates a target, encodes drawing into it, and reads it back.
+fn draw(
+    context: &Context,
+    size: UVec2,
+    encode: impl FnOnce(&mut wgpu::CommandEncoder, &wgpu::TextureView, wgpu::Extent3d),
+) -> Vec<[u8; 4]> {
+    let target = context.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let bytes_per_row = (size.x * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (bytes_per_row * size.y) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encode(
+        &mut encoder,
+        &target.create_view(&wgpu::TextureViewDescriptor::default()),
+        target.size(),
+    );
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        target.size(),
+    );
+    context.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    context.device.poll(wgpu::Maintain::Wait);
+    let data = slice.get_mapped_range();
+    (0..size.y)
+        .flat_map(|y| {
+            let row = &data[(y * bytes_per_row) as usize..][..(size.x * 4) as usize];
+            row.chunks_exact(4)
+                .map(|pixel| pixel.try_into().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Prepares groups and renders them like [`render`].
+pub fn render_groups(
+    context: &Context,
+    renderer: &mut spright::Renderer,
+    size: UVec2,
+    clear: wgpu::Color,
+    groups: &[spright::Group<'_>],
+) -> Vec<[u8; 4]> {
+    render(
+        context,
+        renderer,
+        size,
+        clear,
+        |renderer, context, target_size| {
+            renderer.prepare(&context.device, &context.queue, target_size, groups)
+        },
+    )
+}
+
+/// Asserts that pixels match the expected ones, allowing for rounding of each channel.
+#[track_caller]
+pub fn assert_pixels(actual: &[[u8; 4]], expected: &[[u8; 4]]) {
+    assert_eq!(actual.len(), expected.len());
+    let close = actual
+        .iter()
+        .zip(expected)
+        .all(|(a, e)| a.iter().zip(e).all(|(a, e)| a.abs_diff(*e) <= 1));
+    assert!(close, "expected {expected:?}, got {actual:?}");
+}
@@ -0,0 +1,619 @@
+mod common;
+
+use common::*;
+use glam::*;
+use spright::{BlendMode, Color, Group, Renderer};
+
+/// An opaque black clear color.
+const BLACK: wgpu::Color = wgpu::Color::BLACK;
+
+#[test]
+fn semi_transparent_tint_over_gradient_mask() {
+    let Some(context) = context() else {
+        return;
+    };
+    let mask = texture(
+        context,
+        uvec2(4, 1),
+        wgpu::TextureFormat::R8Unorm,
+        &[0, 85, 170, 255],
+    );
+
+    // The tint's alpha scales the mask, and its color is kept as is: 50% red gets 50% of the way to red at full coverage.
+    for (blend_mode, tint) in [
+        (BlendMode::Alpha, Color::new(255, 0, 0, 128)),
+        (BlendMode::Premultiplied, Color::new(128, 0, 0, 128)),
+    ] {
+        let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+        let pixels = render_groups(
+            context,
+            &mut renderer,
+            uvec2(4, 1),
+            BLACK,
+            &[Group {
+                blend_mode,
+                ..Group::new(&mask, vec![item(uvec2(4, 1), Affine2::IDENTITY, tint)])
+            }],
+        );
+        assert_pixels(
+            &pixels,
+            &[
+                [0, 0, 0, 255],
+                [43, 0, 0, 255],
+                [85, 0, 0, 255],
+                [128, 0, 0, 255],
+            ],
+        );
+    }
+}
+
+#[test]
+fn prepare_with_displaces_vertices() {
+    let Some(context) = context() else {
+        return;
+    };
+    let white = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255; 4],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+    let groups = [Group::new(
+        &white,
+        vec![
+            item(uvec2(1, 1), Affine2::IDENTITY, tint),
+            item(uvec2(1, 1), Affine2::from_translation(vec2(0.0, 1.0)), tint),
+        ],
+    )];
+
+    // Each row is moved along a sine wave, by 0 pixels for the first row and 3 pixels for the second.
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render(
+        context,
+        &mut renderer,
+        uvec2(4, 2),
+        BLACK,
+        |renderer, context, target_size| {
+            renderer.prepare_with(
+                &context.device,
+                &context.queue,
+                target_size,
+                &groups,
+                |vertices| {
+                    for (row, quad) in vertices.chunks_mut(4).enumerate() {
+                        let offset = 3.0 * (row as f32 * std::f32::consts::FRAC_PI_2).sin();
+                        for vertex in quad {
+                            vertex.position[0] += offset;
+                        }
+                    }
+                },
+            )
+        },
+    );
+
+    let (on, off) = ([255; 4], [0, 0, 0, 255]);
+    assert_pixels(&pixels, &[on, off, off, off, off, off, off, on]);
+}
+
+#[test]
+fn both_windings_are_visible_with_culling() {
+    let Some(context) = context() else {
+        return;
+    };
+    let white = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255; 4],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+
+    // A sprite as is at pixel 0, and one mirrored horizontally onto pixel 2.
+    let items = vec![
+        item(uvec2(1, 1), Affine2::IDENTITY, tint),
+        item(
+            uvec2(1, 1),
+            Affine2::from_scale_angle_translation(vec2(-1.0, 1.0), 0.0, vec2(3.0, 0.0)),
+            tint,
+        ),
+    ];
+
+    for front_face in [wgpu::FrontFace::Ccw, wgpu::FrontFace::Cw] {
+        let mut renderer = Renderer::with_options(
+            &context.device,
+            TARGET_FORMAT,
+            &spright::Options {
+                front_face,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+        );
+        let pixels = render_groups(
+            context,
+            &mut renderer,
+            uvec2(4, 1),
+            BLACK,
+            &[Group::new(&white, items.clone())],
+        );
+        let (on, off) = ([255; 4], [0, 0, 0, 255]);
+        assert_pixels(&pixels, &[on, off, on, off]);
+    }
+}
+
+#[test]
+fn premultiplied_additive_sprites_accumulate() {
+    let Some(context) = context() else {
+        return;
+    };
+    let glow = spright::texture::create_texture_rgba8_premultiplied(
+        &context.device,
+        &context.queue,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[160, 100, 40, 128],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_groups(
+        context,
+        &mut renderer,
+        uvec2(1, 1),
+        wgpu::Color::TRANSPARENT,
+        &[Group {
+            blend_mode: BlendMode::PremultipliedAdditive,
+            ..Group::new(&glow, vec![item(uvec2(1, 1), Affine2::IDENTITY, tint); 3])
+        }],
+    );
+
+    // Each sprite adds its premultiplied color, (80, 50, 20, 128), and alpha saturates.
+    assert_pixels(&pixels, &[[240, 150, 60, 255]]);
+}
+
+#[test]
+fn antialiased_mask_edges() {
+    let Some(context) = context() else {
+        return;
+    };
+    // A glyph edge going from uncovered to fully covered.
+    let edge = texture(
+        context,
+        uvec2(4, 1),
+        wgpu::TextureFormat::R8Unorm,
+        &[0, 64, 191, 255],
+    );
+    let orange = Color::new(255, 128, 0, 255);
+
+    // Partially covered pixels blend the tint with the background by coverage in both modes, so edges don't darken or brighten.
+    for (clear, expected) in [
+        (
+            wgpu::Color::BLACK,
+            [
+                [0, 0, 0, 255],
+                [64, 32, 0, 255],
+                [191, 96, 0, 255],
+                [255, 128, 0, 255],
+            ],
+        ),
+        (
+            wgpu::Color::WHITE,
+            [
+                [255, 255, 255, 255],
+                [255, 223, 191, 255],
+                [255, 160, 64, 255],
+                [255, 128, 0, 255],
+            ],
+        ),
+    ] {
+        for blend_mode in [BlendMode::Alpha, BlendMode::Premultiplied] {
+            let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+            let pixels = render_groups(
+                context,
+                &mut renderer,
+                uvec2(4, 1),
+                clear,
+                &[Group {
+                    blend_mode,
+                    ..Group::new(&edge, vec![item(uvec2(4, 1), Affine2::IDENTITY, orange)])
+                }],
+            );
+            assert_pixels(&pixels, &expected);
+        }
+    }
+
+    // With a semi-transparent tint, straight alpha takes a straight tint and premultiplied alpha a premultiplied one, and they agree.
+    let render_edge = |blend_mode, tint| {
+        let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+        render_groups(
+            context,
+            &mut renderer,
+            uvec2(4, 1),
+            wgpu::Color::WHITE,
+            &[Group {
+                blend_mode,
+                ..Group::new(&edge, vec![item(uvec2(4, 1), Affine2::IDENTITY, tint)])
+            }],
+        )
+    };
+    assert_pixels(
+        &render_edge(BlendMode::Premultiplied, Color::new(128, 64, 0, 128)),
+        &render_edge(BlendMode::Alpha, Color::new(255, 128, 0, 128)),
+    );
+}
+
+#[test]
+fn mask_blend_mode_overrides_mask_groups() {
+    let Some(context) = context() else {
+        return;
+    };
+    let mask = texture(context, uvec2(1, 1), wgpu::TextureFormat::R8Unorm, &[128]);
+    let color = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 128],
+    );
+    let tint = Color::new(255, 0, 0, 255);
+
+    // Both groups are alpha blended, but the mask group is drawn additively instead, keeping the blue it's drawn over.
+    let mut renderer = Renderer::with_options(
+        &context.device,
+        TARGET_FORMAT,
+        &spright::Options {
+            mask_blend_mode: Some(BlendMode::Additive),
+            ..Default::default()
+        },
+    );
+    let pixels = render_groups(
+        context,
+        &mut renderer,
+        uvec2(2, 1),
+        wgpu::Color::BLUE,
+        &[
+            Group::new(&mask, vec![item(uvec2(1, 1), Affine2::IDENTITY, tint)]),
+            Group::new(
+                &color,
+                vec![item(
+                    uvec2(1, 1),
+                    Affine2::from_translation(vec2(1.0, 0.0)),
+                    tint,
+                )],
+            ),
+        ],
+    );
+    assert_pixels(&pixels, &[[128, 0, 255, 255], [128, 0, 127, 255]]);
+}
+
+#[test]
+fn groups_use_their_own_uniforms_at_the_adapters_offset_alignment() {
+    let Some(context) = aligned_context() else {
+        return;
+    };
+    let red = texture(
+        context,
+        uvec2(2, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 255, 255, 0, 0, 255],
+    );
+    let green = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 255, 0, 255],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_groups(
+        context,
+        &mut renderer,
+        uvec2(2, 1),
+        BLACK,
+        &[
+            Group::new(&red, vec![item(uvec2(1, 1), Affine2::IDENTITY, tint)]),
+            Group::new(
+                &green,
+                vec![item(
+                    uvec2(1, 1),
+                    Affine2::from_translation(vec2(1.0, 0.0)),
+                    tint,
+                )],
+            ),
+        ],
+    );
+    assert_pixels(&pixels, &[[255, 0, 0, 255], [0, 255, 0, 255]]);
+}
+
+#[test]
+fn tilemap_draws_filled_cells() {
+    let Some(context) = context() else {
+        return;
+    };
+    // A tileset of two 1x1 tiles, red and green.
+    let tileset = texture(
+        context,
+        uvec2(2, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 255, 0, 255, 0, 255],
+    );
+    let renderer = spright::tilemap::TilemapRenderer::new(&context.device, TARGET_FORMAT);
+    let tilemap = spright::tilemap::Tilemap::new(
+        &context.device,
+        &renderer,
+        &tileset,
+        0,
+        uvec2(2, 2),
+        UVec2::ONE,
+    );
+    tilemap.set_tiles(&context.queue, &[1, spright::tilemap::EMPTY, 0, 1]);
+    tilemap.set_tile(&context.queue, uvec2(1, 1), spright::tilemap::EMPTY);
+
+    let pixels = render_pass(context, uvec2(3, 2), BLACK, |rpass, target_size| {
+        tilemap.prepare(
+            &context.queue,
+            target_size,
+            Affine2::from_translation(vec2(1.0, 0.0)),
+        );
+        renderer.render(rpass, &tilemap);
+    });
+    let (red, green, black) = ([255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 0, 255]);
+    assert_pixels(&pixels, &[black, green, black, black, red, black]);
+}
+
+#[test]
+fn update_tint_retints_without_preparing_again() {
+    let Some(context) = context() else {
+        return;
+    };
+    let white = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255; 4],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+    let groups = [Group::new(
+        &white,
+        vec![
+            item(uvec2(1, 1), Affine2::IDENTITY, tint),
+            item(uvec2(1, 1), Affine2::from_translation(vec2(1.0, 0.0)), tint),
+        ],
+    )];
+
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_groups(context, &mut renderer, uvec2(2, 1), BLACK, &groups);
+    assert_pixels(&pixels, &[[255; 4], [255; 4]]);
+
+    // Only the second item changes, and the first keeps the tint it was prepared with.
+    let pixels = render(
+        context,
+        &mut renderer,
+        uvec2(2, 1),
+        BLACK,
+        |renderer, context, _| {
+            renderer.update_tint(&context.queue, 0, 1, Color::new(0, 0, 255, 255));
+        },
+    );
+    assert_pixels(&pixels, &[[255; 4], [0, 0, 255, 255]]);
+}
+
+#[test]
+fn uint16_indices() {
+    let Some(context) = context() else {
+        return;
+    };
+    let white = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255; 4],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+    let renderer = |index_format| {
+        Renderer::with_options(
+            &context.device,
+            TARGET_FORMAT,
+            &spright::Options {
+                index_format,
+                ..Default::default()
+            },
+        )
+    };
+
+    // 16-bit indices draw the same as 32-bit ones.
+    let groups = [Group::new(
+        &white,
+        (0..4)
+            .map(|i| {
+                item(
+                    uvec2(1, 1),
+                    Affine2::from_translation(vec2((i % 2 * 2 + i / 2) as f32, 0.0)),
+                    Color::new(255, (i * 64) as u8, 0, 255),
+                )
+            })
+            .collect(),
+    )];
+    assert_pixels(
+        &render_groups(
+            context,
+            &mut renderer(wgpu::IndexFormat::Uint16),
+            uvec2(4, 1),
+            BLACK,
+            &groups,
+        ),
+        &render_groups(
+            context,
+            &mut renderer(wgpu::IndexFormat::Uint32),
+            uvec2(4, 1),
+            BLACK,
+            &groups,
+        ),
+    );
+
+    // 65536 vertices fit exactly, and one more sprite doesn't.
+    let sprites = |count| {
+        [Group::new(
+            &white,
+            vec![item(uvec2(1, 1), Affine2::IDENTITY, tint); count],
+        )]
+    };
+    let target_size = wgpu::Extent3d {
+        width: 1,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let mut renderer = renderer(wgpu::IndexFormat::Uint16);
+    renderer.prepare(
+        &context.device,
+        &context.queue,
+        target_size,
+        &sprites(16384),
+    );
+    let groups = sprites(16385);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        renderer.prepare(&context.device, &context.queue, target_size, &groups);
+    }));
+    let message = result.unwrap_err().downcast::<&str>().unwrap();
+    assert_eq!(
+        *message,
+        "spright: prepared sprites have more vertices than fit in Options::index_format"
+    );
+}
+
+#[test]
+fn color_backgrounds_are_drawn_behind_groups() {
+    let Some(context) = aligned_context() else {
+        return;
+    };
+    let red = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 255],
+    );
+    let green = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 255, 0, 255],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+    let groups = [
+        Group::new(&red, vec![item(uvec2(1, 1), Affine2::IDENTITY, tint)]),
+        Group::new(
+            &green,
+            vec![item(
+                uvec2(1, 1),
+                Affine2::from_translation(vec2(2.0, 0.0)),
+                tint,
+            )],
+        ),
+    ];
+    let (red, green, blue) = ([255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]);
+
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    renderer.set_background(
+        &context.device,
+        &context.queue,
+        Some(spright::Background::Color(Color::new(0, 0, 255, 255))),
+    );
+    let pixels = render_groups(context, &mut renderer, uvec2(4, 1), BLACK, &groups);
+    assert_pixels(&pixels, &[red, blue, green, blue]);
+}
+
+#[test]
+fn fitted_backgrounds_keep_their_aspect_ratio() {
+    let Some(context) = aligned_context() else {
+        return;
+    };
+    let red = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255, 0, 0, 255],
+    );
+    let green = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 255, 0, 255],
+    );
+    let tint = Color::new(255, 255, 255, 255);
+    let groups = [
+        Group::new(&red, vec![item(uvec2(1, 1), Affine2::IDENTITY, tint)]),
+        Group::new(
+            &green,
+            vec![item(
+                uvec2(1, 1),
+                Affine2::from_translation(vec2(2.0, 0.0)),
+                tint,
+            )],
+        ),
+    ];
+    let (red, green, blue, white, black) = (
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255; 4],
+        [0, 0, 0, 255],
+    );
+
+    // A 2x1 texture contained in a 4x4 target is centered, leaving the rows above and below it uncovered.
+    let image = texture(
+        context,
+        uvec2(2, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[0, 0, 255, 255, 255, 255, 255, 255],
+    );
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    renderer.set_background(
+        &context.device,
+        &context.queue,
+        Some(spright::Background::FittedTexture(
+            &image,
+            spright::fit::FitMode::Contain,
+        )),
+    );
+    let pixels = render_groups(context, &mut renderer, uvec2(4, 4), BLACK, &groups);
+    assert_pixels(
+        &pixels,
+        &[
+            [red, black, green, black],
+            [blue, blue, white, white],
+            [blue, blue, white, white],
+            [black; 4],
+        ]
+        .concat(),
+    );
+}
+
+#[test]
+fn render_bundles_target_the_prepared_format() {
+    let Some(context) = context() else {
+        return;
+    };
+    let white = texture(
+        context,
+        uvec2(1, 1),
+        wgpu::TextureFormat::Rgba8Unorm,
+        &[255; 4],
+    );
+    let groups = [Group::new(
+        &white,
+        vec![item(
+            uvec2(1, 1),
+            Affine2::IDENTITY,
+            Color::new(255, 255, 255, 255),
+        )],
+    )];
+
+    // The format changes for later frames after preparing, but the bundle is still for the target it was prepared for.
+    let mut renderer = Renderer::new(&context.device, TARGET_FORMAT);
+    let pixels = render_pass(context, uvec2(2, 1), BLACK, |rpass, target_size| {
+        renderer.prepare(&context.device, &context.queue, target_size, &groups);
+        renderer.set_texture_format(wgpu::TextureFormat::R8Unorm);
+        rpass.execute_bundles([&renderer.render_bundle(&context.device)]);
+    });
+    assert_pixels(&pixels, &[[255; 4], [0, 0, 0, 255]]);
+}